@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::card::JokerColor;
+
+// what a joker does with the card it targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JokerEffect {
+    Heal,
+    Forge,
+    Bury,
+}
+
+impl JokerEffect {
+    pub fn of(color: JokerColor) -> &'static [Self] {
+        match color {
+            JokerColor::Red => &[Self::Heal],
+            JokerColor::Black => &[Self::Forge, Self::Bury],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Heal => "heal",
+            Self::Forge => "forge",
+            Self::Bury => "bury",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Heal, Self::Forge, Self::Bury].into_iter().find(|effect| effect.name() == name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Heal => "destroys the card and heals half its value",
+            Self::Forge => "destroys the card and adds half its value to your weapon damage, up to 10",
+            Self::Bury => "sends the card to the bottom of the dungeon",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Use(usize),
+    UseJoker { slot: usize, target: usize, effect: JokerEffect },
+    Target { target: usize, effect: JokerEffect },
+    Cancel,
+    Flee,
+    Buy(usize),
+    Continue,
+    Retry,
+    Undo,
+    Win, // debug
+    Steal(usize), // debug
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    NoRoomCard(usize),
+    NoShopCard(usize),
+    CannotDestroyItself,
+    WrongEffect(JokerEffect),
+    RoomNotFull,
+    FledTwice,
+    NoFleesLeft,
+    Anchored,
+    CannotAfford,
+    NothingToUndo,
+    UndoDisabled,
+    Unavailable(Action),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoRoomCard(idx) => write!(f, "No card in room slot {}", idx),
+            Self::NoShopCard(idx) => write!(f, "No card in shop slot {}", idx),
+            Self::CannotDestroyItself => write!(f, "Cannot destroy itself"),
+            Self::WrongEffect(effect) => write!(f, "This joker cannot {}", effect.name()),
+            Self::RoomNotFull => write!(f, "Can only flee from a full room"),
+            Self::FledTwice => write!(f, "Cannot flee twice in a row"),
+            Self::NoFleesLeft => write!(f, "No flees left on this floor"),
+            Self::Anchored => write!(f, "A boss in the room prevents fleeing"),
+            Self::CannotAfford => write!(f, "Can't afford card"),
+            Self::NothingToUndo => write!(f, "Nothing to undo"),
+            Self::UndoDisabled => write!(f, "Undo is disabled in hardcore mode"),
+            Self::Unavailable(_) => write!(f, "Invalid command"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
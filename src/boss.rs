@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::card::{Card, CardType, Rank, Suit};
+use crate::rules::{Mode, Ruleset};
+
+// what a boss does on top of dealing its damage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ability {
+    Armored,
+    Venom,
+    Summon,
+    Anchor,
+    Curse,
+    Thief,
+    Shatter,
+}
+
+impl Ability {
+    // bosses are the black cards held back by the rules, each rank and suit has its own ability
+    pub fn of(card: &Card, rules: &Ruleset) -> Option<Self> {
+        if rules.mode == Mode::Classic || !rules.boss_abilities {
+            return None
+        }
+        let CardType::Regular { suit, rank } = card.card_type else {
+            return None
+        };
+        if !matches!(suit, Suit::Clubs | Suit::Spades) || (rank as u8) < rules.min_boss_rank {
+            return None
+        }
+        match (suit, rank) {
+            (_, Rank::Jack) => Some(Self::Summon),
+            (Suit::Clubs, Rank::Queen) => Some(Self::Anchor),
+            (Suit::Spades, Rank::Queen) => Some(Self::Curse),
+            (Suit::Clubs, Rank::King) => Some(Self::Thief),
+            (Suit::Spades, Rank::King) => Some(Self::Shatter),
+            (Suit::Clubs, _) => Some(Self::Armored),
+            (_, _) => Some(Self::Venom),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Armored => "weapons deal half damage to it",
+            Self::Venom => "deals 2 extra damage when fought barehanded",
+            Self::Summon => "brings the last discarded monster back to the bottom of the dungeon when it enters the room",
+            Self::Anchor => "cannot be fled from",
+            Self::Curse => "hearts heal nothing while it is in the room",
+            Self::Thief => "steals half your money when fought",
+            Self::Shatter => "shatters the weapon used against it",
+        }
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_bosses_have_abilities() {
+        let rules = Ruleset::default();
+        assert_eq!(Ability::of(&Card::new(Suit::Spades, Rank::King), &rules), Some(Ability::Shatter));
+        assert_eq!(Ability::of(&Card::new(Suit::Clubs, Rank::Ten), &rules), Some(Ability::Armored));
+        assert_eq!(Ability::of(&Card::new(Suit::Clubs, Rank::Nine), &rules), None);
+        assert_eq!(Ability::of(&Card::new(Suit::Hearts, Rank::King), &rules), None);
+
+        let plain = Ruleset { boss_abilities: false, ..Ruleset::default() };
+        assert_eq!(Ability::of(&Card::new(Suit::Spades, Rank::King), &plain), None);
+    }
+}
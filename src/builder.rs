@@ -0,0 +1,290 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::card::{Card, CardType};
+use crate::game::{Game, GameState};
+use crate::rules::{Ruleset, RulesError};
+
+#[derive(Debug, Clone)]
+pub struct GameBuilder {
+    dungeon: Vec<Card>,
+    dungeon_discard: Vec<Card>,
+    room: Vec<Card>,
+    bosses: Vec<Card>,
+    shop: Vec<Card>,
+    shop_stock: Vec<Card>,
+    shop_discard: Vec<Card>,
+    health: Option<u8>,
+    money: Option<u32>,
+    weapon_damage: u8,
+    weapon_durability: u8,
+    weapon_stack: Vec<Card>,
+    fled: bool,
+    flees: u8,
+    healed: bool,
+    state: GameState,
+    seed: u64,
+    rules: Ruleset,
+    allow_duplicates: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    DuplicateCard(Card),
+    RoomOverflow(usize),
+    ShopOverflow(usize),
+    NotAJoker(usize),
+    InvalidRules(&'static str),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateCard(card) => write!(f, "{:?} appears more than once", card),
+            Self::RoomOverflow(len) => write!(f, "Room holds more cards than the rules allow, got {}", len),
+            Self::ShopOverflow(len) => write!(f, "Shop sells more cards than the rules allow, got {}", len),
+            Self::NotAJoker(slot) => write!(f, "Room slot {} is not a joker", slot),
+            Self::InvalidRules(reason) => write!(f, "Ruleset is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameBuilder {
+    // starts from an empty floor with full health and the starting money of the rules
+    pub fn new() -> Self {
+        GameBuilder {
+            dungeon: vec![],
+            dungeon_discard: vec![],
+            room: vec![],
+            bosses: vec![],
+            shop: vec![],
+            shop_stock: vec![],
+            shop_discard: vec![],
+            health: None,
+            money: None,
+            weapon_damage: 0,
+            weapon_durability: u8::MAX,
+            weapon_stack: vec![],
+            fled: false,
+            flees: 0,
+            healed: false,
+            state: GameState::Floor,
+            seed: 0,
+            rules: Ruleset::default(),
+            allow_duplicates: false,
+        }
+    }
+
+    pub fn dungeon(mut self, cards: Vec<Card>) -> Self {
+        self.dungeon = cards;
+        self
+    }
+
+    pub fn dungeon_discard(mut self, cards: Vec<Card>) -> Self {
+        self.dungeon_discard = cards;
+        self
+    }
+
+    pub fn room(mut self, cards: Vec<Card>) -> Self {
+        self.room = cards;
+        self
+    }
+
+    pub fn bosses(mut self, cards: Vec<Card>) -> Self {
+        self.bosses = cards;
+        self
+    }
+
+    pub fn shop(mut self, cards: Vec<Card>) -> Self {
+        self.shop = cards;
+        self
+    }
+
+    pub fn shop_stock(mut self, cards: Vec<Card>) -> Self {
+        self.shop_stock = cards;
+        self
+    }
+
+    pub fn shop_discard(mut self, cards: Vec<Card>) -> Self {
+        self.shop_discard = cards;
+        self
+    }
+
+    pub fn health(mut self, health: u8) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn money(mut self, money: u32) -> Self {
+        self.money = Some(money);
+        self
+    }
+
+    // durability is the rank the next monster must be below, u8::MAX for a fresh weapon
+    pub fn weapon(mut self, damage: u8, durability: u8) -> Self {
+        self.weapon_damage = damage;
+        self.weapon_durability = durability;
+        self
+    }
+
+    pub fn weapon_stack(mut self, cards: Vec<Card>) -> Self {
+        self.weapon_stack = cards;
+        self
+    }
+
+    pub fn healed(mut self, healed: bool) -> Self {
+        self.healed = healed;
+        self
+    }
+
+    pub fn fled(mut self, fled: bool) -> Self {
+        self.fled = fled;
+        self
+    }
+
+    pub fn flees(mut self, flees: u8) -> Self {
+        self.flees = flees;
+        self
+    }
+
+    pub fn state(mut self, state: GameState) -> Self {
+        self.state = state;
+        self
+    }
+
+    // seeds the rng used for later shuffles
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn rules(mut self, rules: Ruleset) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn allow_duplicates(mut self, allow: bool) -> Self {
+        self.allow_duplicates = allow;
+        self
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        if let Err(RulesError::Invalid(reason)) = self.rules.validate() {
+            return Err(BuildError::InvalidRules(reason))
+        }
+        if self.room.len() > self.rules.room_size {
+            return Err(BuildError::RoomOverflow(self.room.len()))
+        }
+        if self.shop_stock.len() > self.rules.shop_size {
+            return Err(BuildError::ShopOverflow(self.shop_stock.len()))
+        }
+        if let GameState::Choosing { joker } = self.state {
+            if !matches!(joker.checked_sub(1).and_then(|i| self.room.get(i)), Some(Card { card_type: CardType::Joker { .. } })) {
+                return Err(BuildError::NotAJoker(joker))
+            }
+        }
+
+        if !self.allow_duplicates {
+            let mut seen = HashSet::new();
+            let piles = [&self.dungeon, &self.dungeon_discard, &self.weapon_stack, &self.room, &self.bosses, &self.shop, &self.shop_stock, &self.shop_discard];
+            for card in piles.into_iter().flatten() {
+                if !seen.insert(card) {
+                    return Err(BuildError::DuplicateCard(card.clone()))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Game, BuildError> {
+        self.validate()?;
+
+        let mut game = Game::with_rules(self.seed, self.rules);
+        game.dungeon = self.dungeon;
+        game.dungeon_discard = self.dungeon_discard;
+        game.room = self.room;
+        game.bosses = self.bosses;
+        game.shop = self.shop;
+        game.shop_stock = self.shop_stock;
+        game.shop_discard = self.shop_discard;
+        game.health = self.health.unwrap_or(game.rules.max_health);
+        game.money = self.money.unwrap_or(game.rules.starting_money);
+        game.weapon_damage = self.weapon_damage;
+        game.weapon_durability = self.weapon_durability;
+        game.weapon_stack = self.weapon_stack;
+        game.fled = self.fled;
+        game.flees = self.flees;
+        game.healed = self.healed;
+        game.state = self.state;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+    use crate::card::{JokerColor, Rank, Suit};
+
+    #[test]
+    fn builds_requested_state() {
+        let game = GameBuilder::new()
+            .dungeon(vec![Card::new(Suit::Clubs, Rank::Four)])
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::joker(JokerColor::Red)])
+            .health(7)
+            .money(20)
+            .weapon(5, 9)
+            .state(GameState::Choosing { joker: 2 })
+            .build()
+            .unwrap();
+
+        assert_eq!(game.dungeon, vec![Card::new(Suit::Clubs, Rank::Four)]);
+        assert_eq!(game.health, 7);
+        assert_eq!(game.money, 20);
+        assert_eq!((game.weapon_damage, game.weapon_durability), (5, 9));
+        assert_eq!(game.state, GameState::Choosing { joker: 2 });
+    }
+
+    #[test]
+    fn built_game_plays() {
+        let mut game = GameBuilder::new()
+            .dungeon(vec![Card::new(Suit::Hearts, Rank::Four); 3])
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::new(Suit::Clubs, Rank::Five)])
+            .weapon(6, u8::MAX)
+            .allow_duplicates(true)
+            .build()
+            .unwrap();
+
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.health, 9);
+        assert_eq!(game.room.len(), 4);
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let seven = Card::new(Suit::Clubs, Rank::Seven);
+        let builder = GameBuilder::new().dungeon(vec![seven.clone()]).shop_discard(vec![seven.clone()]);
+        assert_eq!(builder.clone().build().err(), Some(BuildError::DuplicateCard(seven)));
+        assert!(builder.allow_duplicates(true).build().is_ok());
+    }
+
+    #[test]
+    fn rejects_impossible_layouts() {
+        let room = vec![Card::new(Suit::Clubs, Rank::Four), Card::new(Suit::Clubs, Rank::Five), Card::new(Suit::Clubs, Rank::Six), Card::new(Suit::Clubs, Rank::Seven), Card::new(Suit::Clubs, Rank::Eight)];
+        assert_eq!(GameBuilder::new().room(room).build().err(), Some(BuildError::RoomOverflow(5)));
+
+        let builder = GameBuilder::new().room(vec![Card::new(Suit::Clubs, Rank::Four)]).state(GameState::Choosing { joker: 1 });
+        assert_eq!(builder.build().err(), Some(BuildError::NotAJoker(1)));
+
+        let rules = Ruleset { room_size: 2, restock_threshold: 3, ..Ruleset::default() };
+        assert!(matches!(GameBuilder::new().rules(rules).build(), Err(BuildError::InvalidRules(_))));
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Suit {
+    Hearts,
+    Diamonds,
+    Clubs,
+    Spades,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, EnumIter, Eq, Hash, Serialize, Deserialize)]
+pub enum Rank {
+    Ace = 1,
+    Two = 2,
+    Three = 3,
+    Four = 4,
+    Five = 5,
+    Six = 6,
+    Seven = 7,
+    Eight = 8,
+    Nine = 9,
+    Ten = 10,
+    Jack = 11,
+    Queen = 12,
+    King = 13,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum JokerColor {
+    Red,
+    Black,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CardType {
+    Regular {
+        suit: Suit,
+        rank: Rank,
+    },
+    Joker {
+        color: JokerColor,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Card {
+    pub card_type: CardType,
+}
+
+impl Card {
+    pub fn new(suit: Suit, rank: Rank) -> Self {
+        Card {
+            card_type: CardType::Regular { suit, rank },
+        }
+    }
+
+    pub fn joker(color: JokerColor) -> Self {
+        Card {
+            card_type: CardType::Joker { color },
+        }
+    }
+}
+
+impl Ord for Card {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.card_type {
+            CardType::Regular { suit, rank } => match other.card_type {
+                CardType::Regular { suit: other_suit, rank: other_rank } => {
+                    rank.cmp(&other_rank).then_with(|| suit.cmp(&other_suit))
+                }
+                CardType::Joker { .. } => Ordering::Greater
+            },
+            CardType::Joker { color } => match other.card_type {
+                CardType::Regular { .. } => Ordering::Less,
+                CardType::Joker { color: other_color } => color.cmp(&other_color)
+            }
+        }
+    }
+}
+
+impl PartialOrd for Card {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,29 @@
+use crate::card::Card;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    Fought { card: Card, weapon: Option<u8>, damage: u8, money: u32 },
+    // a heart beyond the first in a classic room
+    Wasted { card: Card },
+    // a heart used while a cursing boss is in the room
+    Cursed { boss: Card, card: Card },
+    Healed { card: Card, amount: u8, full: bool },
+    Equipped { card: Card },
+    Repaired { card: Card, amount: u8 },
+    Destroyed { card: Card },
+    // the weapon damage after a black joker forged a card into it
+    Forged { card: Card, damage: u8 },
+    Buried { card: Card },
+    Fled,
+    RoomRestocked,
+    FloorComplete,
+    Lost,
+    Won,
+    Purchased { card: Card, price: u32 },
+    ShopRestocked,
+    BossesAdded(Vec<Card>),
+    Summoned { boss: Card, card: Card },
+    Robbed { boss: Card, money: u32 },
+    Shattered { boss: Card },
+    Undone,
+}
@@ -0,0 +1,1176 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use strum::IntoEnumIterator;
+
+use crate::action::{Action, ActionError, JokerEffect};
+use crate::card::{Card, CardType, JokerColor, Rank, Suit};
+use crate::event::GameEvent;
+use crate::boss::Ability;
+use crate::rules::{Mode, Ruleset};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub dungeon: Vec<Card>,
+    pub dungeon_discard: Vec<Card>,
+    pub room: Vec<Card>,
+    pub bosses: Vec<Card>,
+    pub shop: Vec<Card>,
+    pub shop_stock: Vec<Card>,
+    pub shop_discard: Vec<Card>,
+    pub health: u8,
+    pub money: u32,
+    pub weapon_damage: u8,
+    pub weapon_durability: u8,
+    // classic only: monsters slain with the current weapon, most recent last
+    pub weapon_stack: Vec<Card>,
+    pub fled: bool,
+    // times fled on this floor
+    pub flees: u8,
+    // whether a heart was used in the current room
+    pub healed: bool,
+    pub state: GameState,
+    pub seed: u64,
+    pub undo_depth: usize,
+    pub undo_used: bool,
+    pub rules: Ruleset,
+    rng: ChaCha8Rng,
+    #[serde(skip)]
+    history: Vec<Game>,
+}
+
+pub const DEFAULT_UNDO_DEPTH: usize = 10;
+// as strong as the best diamond
+const MAX_FORGED_DAMAGE: u8 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameState {
+    Floor,
+    Choosing { joker: usize },
+    Shop,
+    Lost,
+    Won,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rules(seed, Ruleset::default())
+    }
+
+    // the same seed, rules and actions always produce the same game
+    pub fn with_rules(seed: u64, rules: Ruleset) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut deck: Vec<Card> = Self::create_deck();
+        deck.shuffle(&mut rng);
+
+        let mut dungeon = vec![];
+        let mut bosses = vec![];
+        let mut shop = vec![];
+        for card in deck {
+            match card.card_type {
+                // every black card and the red number cards, all in the dungeon
+                CardType::Regular { suit, rank } if rules.mode == Mode::Classic => {
+                    if matches!(suit, Suit::Clubs | Suit::Spades) || (Rank::Two..=Rank::Ten).contains(&rank) {
+                        dungeon.push(card);
+                    }
+                },
+                CardType::Joker { .. } if rules.mode == Mode::Classic => {},
+                CardType::Regular { suit, rank } => {
+                    let rank = rank as u8;
+                    if (rules.min_dungeon_rank..=rules.max_dungeon_rank).contains(&rank) {
+                        dungeon.push(card);
+                    } else if matches!(suit, Suit::Hearts | Suit::Diamonds) && rank >= rules.min_shop_rank {
+                        shop.push(card);
+                    } else if matches!(suit, Suit::Clubs | Suit::Spades) && rank >= rules.min_boss_rank {
+                        bosses.push(card);
+                    }
+                },
+                CardType::Joker { .. } => {
+                    shop.push(card);
+                }
+            }
+        }
+        bosses.sort();
+        if rules.shuffle_bosses {
+            bosses.shuffle(&mut rng);
+        }
+
+        let mut game = Game {
+            dungeon,
+            dungeon_discard: vec![],
+            room: vec![],
+            bosses,
+            shop,
+            shop_stock: vec![],
+            shop_discard: vec![],
+            health: rules.max_health,
+            money: rules.starting_money,
+            weapon_damage: 0,
+            weapon_durability: u8::MAX,
+            weapon_stack: vec![],
+            fled: false,
+            flees: 0,
+            healed: false,
+            state: GameState::Floor,
+            seed,
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            undo_used: false,
+            rules,
+            rng,
+            history: vec![],
+        };
+        game.start_floor(&mut vec![]);
+        game
+    }
+
+    // uses the room and dungeon exactly as given instead of dealing a shuffled deck
+    pub fn from_layout(room: Vec<Card>, dungeon: Vec<Card>, bosses: Vec<Card>, shop: Vec<Card>) -> Self {
+        let mut game = Game {
+            room,
+            dungeon,
+            bosses,
+            shop,
+            ..Self::with_seed(0)
+        };
+        game.dungeon_discard.clear();
+        game
+    }
+
+    pub fn apply(&mut self, action: Action) -> Result<Vec<GameEvent>, ActionError> {
+        if action == Action::Undo {
+            return self.undo()
+        }
+
+        if action == Action::Retry || self.undo_depth == 0 {
+            return self.perform(action)
+        }
+
+        let snapshot = self.snapshot();
+        let events = self.perform(action)?;
+        self.history.push(snapshot);
+        if self.history.len() > self.undo_depth {
+            self.history.remove(0);
+        }
+        Ok(events)
+    }
+
+    // copy of the game without its undo history
+    pub fn snapshot(&self) -> Game {
+        Game {
+            dungeon: self.dungeon.clone(),
+            dungeon_discard: self.dungeon_discard.clone(),
+            room: self.room.clone(),
+            bosses: self.bosses.clone(),
+            shop: self.shop.clone(),
+            shop_stock: self.shop_stock.clone(),
+            shop_discard: self.shop_discard.clone(),
+            weapon_stack: self.weapon_stack.clone(),
+            state: self.state.clone(),
+            rng: self.rng.clone(),
+            history: vec![],
+            ..*self
+        }
+    }
+
+    // every action a player could take, excluding undo and debug actions
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions = vec![];
+        match self.state {
+            GameState::Floor => {
+                for (i, card) in self.room.iter().enumerate() {
+                    match card.card_type {
+                        CardType::Joker { .. } => {
+                            for target in self.joker_targets(i + 1) {
+                                for &effect in self.joker_effects(i + 1) {
+                                    actions.push(Action::UseJoker { slot: i + 1, target, effect });
+                                }
+                            }
+                        }
+                        CardType::Regular { .. } => actions.push(Action::Use(i + 1)),
+                    }
+                }
+                if self.room.len() >= self.rules.room_size && !self.fled && self.flees_left() != Some(0) && !self.room_has(Ability::Anchor) {
+                    actions.push(Action::Flee);
+                }
+            }
+            GameState::Choosing { joker } => {
+                for target in self.joker_targets(joker) {
+                    actions.extend(self.joker_effects(joker).iter().map(|&effect| Action::Target { target, effect }));
+                }
+                actions.push(Action::Cancel);
+            }
+            GameState::Shop => {
+                for (i, card) in self.shop_stock.iter().enumerate() {
+                    if self.rules.value(card) <= self.money {
+                        actions.push(Action::Buy(i + 1));
+                    }
+                }
+                actions.push(Action::Continue);
+            }
+            GameState::Lost | GameState::Won => actions.push(Action::Retry),
+        }
+        actions
+    }
+
+    // undo marks the run even if every undone action is replayed afterwards
+    fn undo(&mut self) -> Result<Vec<GameEvent>, ActionError> {
+        if self.undo_depth == 0 {
+            return Err(ActionError::UndoDisabled)
+        }
+        let mut snapshot = self.history.pop().ok_or(ActionError::NothingToUndo)?;
+        snapshot.history = std::mem::take(&mut self.history);
+        snapshot.undo_depth = self.undo_depth;
+        snapshot.undo_used = true;
+        *self = snapshot;
+        Ok(vec![GameEvent::Undone])
+    }
+
+    // the classic Scoundrel score: health left once the dungeon is cleared,
+    // or minus the value of every monster still in the dungeon after dying
+    pub fn score(&self) -> Option<i32> {
+        match self.state {
+            GameState::Won => Some(self.health as i32),
+            GameState::Lost => {
+                let monsters: u32 = self.dungeon.iter().chain(&self.room)
+                    .filter(|card| matches!(card.card_type, CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. }))
+                    .map(|card| self.rules.value(card))
+                    .sum();
+                Some(-(monsters as i32))
+            }
+            GameState::Floor | GameState::Choosing { .. } | GameState::Shop => None,
+        }
+    }
+
+    pub fn ability(&self, card: &Card) -> Option<Ability> {
+        Ability::of(card, &self.rules)
+    }
+
+    fn room_has(&self, ability: Ability) -> bool {
+        self.room.iter().any(|card| self.ability(card) == Some(ability))
+    }
+
+    pub fn flees_left(&self) -> Option<u8> {
+        self.rules.flees_per_floor.map(|limit| limit.saturating_sub(self.flees))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_depth > 0 && !self.history.is_empty()
+    }
+
+    fn perform(&mut self, action: Action) -> Result<Vec<GameEvent>, ActionError> {
+        let mut events = vec![];
+        match (self.state.clone(), action) {
+            (GameState::Floor, Action::Use(slot)) => self.use_card(slot, None, &mut events)?,
+            (GameState::Floor, Action::UseJoker { slot, target, effect }) => self.use_card(slot, Some((target, effect)), &mut events)?,
+            (GameState::Choosing { joker }, Action::Target { target, effect }) => self.use_card(joker, Some((target, effect)), &mut events)?,
+            (GameState::Choosing { .. }, Action::Cancel) => self.state = GameState::Floor,
+            (GameState::Floor, Action::Flee) => self.flee(&mut events)?,
+            (GameState::Floor, Action::Win) => self.complete_floor(&mut events), // debug
+            (GameState::Shop, Action::Buy(slot)) => self.buy_card(slot, &mut events)?,
+            (GameState::Shop, Action::Steal(slot)) => self.steal_card(slot)?, // debug
+            (GameState::Shop, Action::Continue) => self.leave_shop(&mut events),
+            (GameState::Lost | GameState::Won, Action::Retry) => {
+                let undo_depth = self.undo_depth;
+                *self = Game::with_rules(self.rng.random(), self.rules);
+                self.undo_depth = undo_depth;
+            }
+            (_, action) => return Err(ActionError::Unavailable(action)),
+        }
+
+        if self.state == GameState::Floor && matches!(action, Action::Use(_) | Action::UseJoker { .. } | Action::Target { .. } | Action::Flee) {
+            self.refresh_room(&mut events);
+        }
+        Ok(events)
+    }
+
+    // room slots a joker in the given slot may destroy
+    pub fn joker_targets(&self, joker: usize) -> Vec<usize> {
+        match joker.checked_sub(1).and_then(|i| self.room.get(i)) {
+            Some(Card { card_type: CardType::Joker { .. } }) => {
+                (1..=self.room.len()).filter(|&idx| idx != joker).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    // what the joker in the given slot can do with its target
+    pub fn joker_effects(&self, joker: usize) -> &'static [JokerEffect] {
+        match joker.checked_sub(1).and_then(|i| self.room.get(i)) {
+            Some(Card { card_type: CardType::Joker { color } }) => JokerEffect::of(*color),
+            _ => &[],
+        }
+    }
+
+    fn leave_shop(&mut self, events: &mut Vec<GameEvent>) {
+        self.shop_discard.append(&mut self.shop_stock);
+        if self.shop.is_empty() {
+            events.push(GameEvent::ShopRestocked);
+            self.shop.append(&mut self.shop_discard);
+            self.shop.shuffle(&mut self.rng);
+        }
+
+        let bosses: Vec<Card> = self.bosses.drain(..cmp::min(self.rules.bosses_per_floor, self.bosses.len())).collect();
+        self.dungeon.extend(bosses.iter().cloned());
+        events.push(GameEvent::BossesAdded(bosses));
+
+        self.state = GameState::Floor;
+        self.start_floor(events);
+    }
+
+    fn start_floor(&mut self, events: &mut Vec<GameEvent>) {
+        self.health = self.rules.max_health;
+        self.weapon_damage = 0;
+        self.weapon_durability = u8::MAX;
+        self.flees = 0;
+
+        self.dungeon.append(&mut self.weapon_stack);
+        self.dungeon.append(&mut self.room);
+        self.dungeon.append(&mut self.dungeon_discard);
+        self.dungeon.shuffle(&mut self.rng);
+        self.restock_room(events);
+    }
+
+    pub fn create_deck() -> Vec<Card> {
+        let mut deck = Vec::with_capacity(54);
+        for suit in [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades] {
+            for rank in Rank::iter() {
+                deck.push(Card {
+                    card_type: CardType::Regular { suit, rank },
+                });
+            }
+        }
+        deck.push(Card {
+            card_type: CardType::Joker { color: JokerColor::Black }
+        });
+        deck.push(Card {
+            card_type: CardType::Joker { color: JokerColor::Red }
+        });
+        deck
+    }
+
+    // returns how many cards were added to the room, after the bosses among them act
+    fn restock_room(&mut self, events: &mut Vec<GameEvent>) -> usize {
+        if self.room.len() > self.rules.restock_threshold {
+            return 0
+        }
+
+        let amount_add = cmp::min(self.rules.room_size - self.room.len(), self.dungeon.len());
+        for _i in 0..amount_add {
+            self.room.push(self.dungeon.remove(0));
+        }
+        if amount_add > 0 {
+            self.healed = false;
+        }
+        for i in self.room.len() - amount_add..self.room.len() {
+            if self.ability(&self.room[i]) == Some(Ability::Summon) {
+                self.summon(i, events);
+            }
+        }
+        amount_add
+    }
+
+    fn refresh_room(&mut self, events: &mut Vec<GameEvent>) {
+        let start = events.len();
+        if self.restock_room(events) > 0 {
+            events.insert(start, GameEvent::RoomRestocked);
+        }
+
+        // check if lost
+        if self.health == 0 {
+            self.state = GameState::Lost;
+            events.push(GameEvent::Lost);
+            return
+        }
+
+        // check if won
+        if self.dungeon.is_empty() && !self.room.iter().any(|card|
+            matches!(card.card_type, CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. })) {
+            self.complete_floor(events);
+        }
+    }
+
+    // the most recently discarded monster goes back to the bottom of the dungeon
+    fn summon(&mut self, boss_idx: usize, events: &mut Vec<GameEvent>) {
+        let monster = self.dungeon_discard.iter().rposition(|card|
+            matches!(card.card_type, CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. }));
+        if let Some(idx) = monster {
+            let card = self.dungeon_discard.remove(idx);
+            self.dungeon.push(card.clone());
+            events.push(GameEvent::Summoned { boss: self.room[boss_idx].clone(), card });
+        }
+    }
+
+    fn complete_floor(&mut self, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::FloorComplete);
+
+        if self.bosses.is_empty() {
+            self.state = GameState::Won;
+            events.push(GameEvent::Won);
+        } else {
+            self.state = GameState::Shop;
+            for _i in 0..cmp::min(self.shop.len(), self.rules.shop_size) {
+                self.shop_stock.push(self.shop.remove(0));
+            }
+        }
+    }
+
+    // target is the room slot a joker acts on and how, ignored for other cards;
+    // a joker without a target waits for one in GameState::Choosing
+    fn use_card(&mut self, mut room_idx: usize, target: Option<(usize, JokerEffect)>, events: &mut Vec<GameEvent>) -> Result<(), ActionError> {
+        if room_idx == 0 || room_idx > self.room.len() {
+            return Err(ActionError::NoRoomCard(room_idx))
+        }
+
+        let card = self.room[room_idx-1].clone();
+        let mut slain = false;
+        match card.card_type {
+            CardType::Joker { color } => {
+                let Some((idx, effect)) = target else {
+                    self.state = GameState::Choosing { joker: room_idx };
+                    return Ok(())
+                };
+                if idx == 0 || idx > self.room.len() {
+                    return Err(ActionError::NoRoomCard(idx))
+                }
+                if idx == room_idx {
+                    return Err(ActionError::CannotDestroyItself)
+                }
+                if !JokerEffect::of(color).contains(&effect) {
+                    return Err(ActionError::WrongEffect(effect))
+                }
+
+                let v = u8::try_from(self.rules.value(&self.room[idx-1]).div_ceil(2)).unwrap_or(u8::MAX);
+                let target = self.room.remove(idx-1);
+                match effect {
+                    JokerEffect::Heal => {
+                        self.health = cmp::min(self.health.saturating_add(v), cmp::max(self.rules.max_health, self.health));
+                        events.push(GameEvent::Destroyed { card: target.clone() });
+                        events.push(GameEvent::Healed { card: card.clone(), amount: v, full: false });
+                        self.dungeon_discard.push(target);
+                    }
+                    // a forged card becomes a fresh weapon when none is equipped
+                    JokerEffect::Forge => {
+                        if self.weapon_damage == 0 {
+                            self.weapon_durability = u8::MAX;
+                        }
+                        self.weapon_damage = cmp::min(self.weapon_damage.saturating_add(v), MAX_FORGED_DAMAGE).max(self.weapon_damage);
+                        events.push(GameEvent::Forged { card: target.clone(), damage: self.weapon_damage });
+                        self.dungeon_discard.push(target);
+                    }
+                    JokerEffect::Bury => {
+                        events.push(GameEvent::Buried { card: target.clone() });
+                        self.dungeon.push(target);
+                    }
+                }
+                if idx < room_idx {
+                    room_idx -= 1;
+                }
+                self.state = GameState::Floor;
+            }
+            CardType::Regular { suit, rank } => match suit {
+                Suit::Clubs | Suit::Spades => {
+                    let value = self.rules.rank_value(rank);
+                    let ability = self.ability(&card);
+                    if self.weapon_damage > 0 && self.weapon_durability > value {
+                        let weapon = if ability == Some(Ability::Armored) { self.weapon_damage / 2 } else { self.weapon_damage };
+                        let d: i16 = value as i16 - weapon as i16;
+                        let mut damage = 0;
+                        let mut money = 0;
+                        // classic has no money, overkill is simply wasted
+                        if d < 0 && self.rules.mode == Mode::Roguelike {
+                            money = d.unsigned_abs() as u32;
+                            self.money += money;
+                        } else if d > 0 {
+                            damage = d as u8;
+                            self.health = self.health.saturating_sub(damage);
+                        }
+                        self.weapon_durability = value;
+                        // only classic keeps a stack, roguelike discards the monster as before
+                        slain = self.rules.mode == Mode::Classic;
+                        events.push(GameEvent::Fought { card: card.clone(), weapon: Some(self.weapon_damage), damage, money });
+                        if ability == Some(Ability::Shatter) {
+                            self.weapon_damage = 0;
+                            self.weapon_durability = u8::MAX;
+                            self.dungeon_discard.append(&mut self.weapon_stack);
+                            slain = false;
+                            events.push(GameEvent::Shattered { boss: card.clone() });
+                        }
+                    } else {
+                        let damage = if ability == Some(Ability::Venom) { value + 2 } else { value };
+                        self.health = self.health.saturating_sub(damage);
+                        events.push(GameEvent::Fought { card: card.clone(), weapon: None, damage, money: 0 });
+                    }
+                    if ability == Some(Ability::Thief) {
+                        let money = self.money.div_ceil(2);
+                        self.money -= money;
+                        events.push(GameEvent::Robbed { boss: card, money });
+                    }
+                }
+                Suit::Hearts => {
+                    let curse = self.room.iter().find(|boss| self.ability(boss) == Some(Ability::Curse));
+                    if let Some(boss) = curse {
+                        events.push(GameEvent::Cursed { boss: boss.clone(), card });
+                    } else if self.rules.mode == Mode::Classic && self.healed {
+                        events.push(GameEvent::Wasted { card });
+                    } else if rank < Rank::Jack {
+                        self.health = cmp::min(self.health.saturating_add(rank as u8), cmp::max(self.rules.max_health, self.health));
+                        self.healed = true;
+                        events.push(GameEvent::Healed { card, amount: rank as u8, full: false });
+                    } else {
+                        let absorption = (rank as u8 - Rank::Ten as u8) * 2;
+                        self.health = self.rules.max_health.saturating_add(absorption);
+                        self.healed = true;
+                        events.push(GameEvent::Healed { card, amount: absorption, full: true });
+                    }
+                },
+                Suit::Diamonds => {
+                    if rank < Rank::Jack {
+                        self.weapon_damage = rank as u8;
+                        self.weapon_durability = u8::MAX;
+                        self.dungeon_discard.append(&mut self.weapon_stack);
+                        events.push(GameEvent::Equipped { card });
+                    } else {
+                        let repair = (rank as u8 - Rank::Ten as u8) * 2;
+                        if self.weapon_durability < u8::MAX {
+                            self.weapon_durability += repair;
+                        }
+                        events.push(GameEvent::Repaired { card, amount: repair });
+                    }
+                }
+            }
+        }
+
+        let used = self.room.remove(room_idx-1);
+        if slain {
+            self.weapon_stack.push(used);
+        } else {
+            self.dungeon_discard.push(used);
+        }
+        self.fled = false;
+        Ok(())
+    }
+
+    fn flee(&mut self, events: &mut Vec<GameEvent>) -> Result<(), ActionError> {
+        if self.room.len() < self.rules.room_size {
+            return Err(ActionError::RoomNotFull)
+        }
+        if self.fled {
+            return Err(ActionError::FledTwice)
+        }
+        if self.flees_left() == Some(0) {
+            return Err(ActionError::NoFleesLeft)
+        }
+        if self.room_has(Ability::Anchor) {
+            return Err(ActionError::Anchored)
+        }
+
+        for _i in 0..self.rules.room_size {
+            self.dungeon.push(self.room.pop().expect("ERR: Too few cards in room"));
+        }
+        self.fled = true;
+        self.flees += 1;
+        events.push(GameEvent::Fled);
+        Ok(())
+    }
+
+    fn buy_card(&mut self, shop_idx: usize, events: &mut Vec<GameEvent>) -> Result<(), ActionError> {
+        if shop_idx == 0 || shop_idx > self.shop_stock.len() {
+            return Err(ActionError::NoShopCard(shop_idx))
+        }
+
+        let price = self.rules.value(&self.shop_stock[shop_idx-1]);
+        if self.money >= price {
+            self.money -= price;
+            let card = self.shop_stock.remove(shop_idx-1);
+            events.push(GameEvent::Purchased { card: card.clone(), price });
+            self.dungeon.push(card);
+            Ok(())
+        } else {
+            Err(ActionError::CannotAfford)
+        }
+    }
+
+    // debug
+    fn steal_card(&mut self, shop_idx: usize) -> Result<(), ActionError> {
+        if shop_idx == 0 || shop_idx > self.shop_stock.len() {
+            return Err(ActionError::NoShopCard(shop_idx))
+        }
+
+        self.dungeon.push(self.shop_stock.remove(shop_idx-1));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save;
+
+    fn card(suit: Suit, rank: Rank) -> Card {
+        Card::new(suit, rank)
+    }
+
+    fn floor(room: Vec<Card>, dungeon: Vec<Card>) -> Game {
+        Game::from_layout(room, dungeon, vec![card(Suit::Clubs, Rank::Ten), card(Suit::Spades, Rank::Ten)], vec![])
+    }
+
+    fn filler() -> Vec<Card> {
+        vec![card(Suit::Clubs, Rank::Four); 8]
+    }
+
+    #[test]
+    fn fight_barehanded() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Seven), card(Suit::Hearts, Rank::Four)], filler());
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Clubs, Rank::Seven), weapon: None, damage: 7, money: 0 });
+        assert_eq!(game.health, 5);
+        assert_eq!(game.dungeon_discard, vec![card(Suit::Clubs, Rank::Seven)]);
+    }
+
+    #[test]
+    fn fight_with_weapon_sets_durability() {
+        let mut game = floor(vec![card(Suit::Spades, Rank::Nine), card(Suit::Clubs, Rank::Seven), card(Suit::Clubs, Rank::Eight)], filler());
+        game.weapon_damage = 6;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Spades, Rank::Nine), weapon: Some(6), damage: 3, money: 0 });
+        assert_eq!(game.health, 9);
+        assert_eq!(game.weapon_durability, 9);
+
+        // weapon only works on monsters weaker than the last one
+        game.apply(Action::Use(2)).unwrap();
+        assert_eq!(game.health, 7);
+        assert_eq!(game.weapon_durability, 8);
+    }
+
+    #[test]
+    fn fight_weaker_monster_pays_out() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Four), card(Suit::Clubs, Rank::Five)], filler());
+        game.weapon_damage = 9;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Clubs, Rank::Four), weapon: Some(9), damage: 0, money: 5 });
+        assert_eq!(game.money, 10);
+        assert_eq!(game.health, 12);
+    }
+
+    #[test]
+    fn weapon_breaks_against_stronger_monster() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Six), card(Suit::Clubs, Rank::Five)], filler());
+        game.weapon_damage = 9;
+        game.weapon_durability = 6;
+
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.health, 6);
+        assert_eq!(game.weapon_durability, 6);
+    }
+
+    #[test]
+    fn heart_heals_up_to_max() {
+        let mut game = floor(vec![card(Suit::Hearts, Rank::Nine), card(Suit::Clubs, Rank::Five)], filler());
+        game.health = 7;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Healed { card: card(Suit::Hearts, Rank::Nine), amount: 9, full: false });
+        assert_eq!(game.health, 12);
+    }
+
+    #[test]
+    fn heart_keeps_overheal() {
+        let mut game = floor(vec![card(Suit::Hearts, Rank::Four), card(Suit::Clubs, Rank::Five)], filler());
+        game.health = 16;
+
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.health, 16);
+    }
+
+    #[test]
+    fn face_heart_absorbs() {
+        for (rank, health) in [(Rank::Jack, 14), (Rank::Queen, 16), (Rank::King, 18)] {
+            let mut game = floor(vec![card(Suit::Hearts, rank), card(Suit::Clubs, Rank::Five)], filler());
+            game.health = 3;
+
+            let events = game.apply(Action::Use(1)).unwrap();
+            assert_eq!(events[0], GameEvent::Healed { card: card(Suit::Hearts, rank), amount: health - 12, full: true });
+            assert_eq!(game.health, health);
+        }
+    }
+
+    #[test]
+    fn diamond_equips_fresh_weapon() {
+        let mut game = floor(vec![card(Suit::Diamonds, Rank::Seven), card(Suit::Clubs, Rank::Five)], filler());
+        game.weapon_damage = 4;
+        game.weapon_durability = 5;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Equipped { card: card(Suit::Diamonds, Rank::Seven) });
+        assert_eq!(game.weapon_damage, 7);
+        assert_eq!(game.weapon_durability, u8::MAX);
+    }
+
+    #[test]
+    fn face_diamond_repairs_used_weapon() {
+        let mut game = floor(vec![card(Suit::Diamonds, Rank::Queen), card(Suit::Clubs, Rank::Five)], filler());
+        game.weapon_damage = 4;
+        game.weapon_durability = 5;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Repaired { card: card(Suit::Diamonds, Rank::Queen), amount: 4 });
+        assert_eq!(game.weapon_durability, 9);
+    }
+
+    #[test]
+    fn face_diamond_ignores_fresh_weapon() {
+        let mut game = floor(vec![card(Suit::Diamonds, Rank::King), card(Suit::Clubs, Rank::Five)], filler());
+        game.weapon_damage = 4;
+
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.weapon_durability, u8::MAX);
+    }
+
+    #[test]
+    fn red_joker_destroys_and_heals() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Nine), card(Suit::Hearts, Rank::Four), Card::joker(JokerColor::Red)], filler());
+        game.health = 4;
+
+        assert_eq!(game.apply(Action::UseJoker { slot: 3, target: 1, effect: JokerEffect::Bury }), Err(ActionError::WrongEffect(JokerEffect::Bury)));
+        let events = game.apply(Action::UseJoker { slot: 3, target: 1, effect: JokerEffect::Heal }).unwrap();
+        assert_eq!(events[0], GameEvent::Destroyed { card: card(Suit::Clubs, Rank::Nine) });
+        assert_eq!(events[1], GameEvent::Healed { card: Card::joker(JokerColor::Red), amount: 5, full: false });
+        assert_eq!(game.health, 9);
+        assert_eq!(game.money, 5);
+        assert_eq!(game.room[0], card(Suit::Hearts, Rank::Four));
+        assert_eq!(game.room.len(), 4);
+        assert_eq!(game.dungeon_discard, vec![card(Suit::Clubs, Rank::Nine), Card::joker(JokerColor::Red)]);
+    }
+
+    #[test]
+    fn joker_waits_for_target() {
+        let mut game = floor(vec![Card::joker(JokerColor::Black), card(Suit::Clubs, Rank::Eight), card(Suit::Hearts, Rank::Four)], filler());
+
+        assert_eq!(game.apply(Action::Use(1)).unwrap(), vec![]);
+        assert_eq!(game.state, GameState::Choosing { joker: 1 });
+        assert_eq!(game.joker_targets(1), vec![2, 3]);
+        assert_eq!(game.joker_effects(1), &[JokerEffect::Forge, JokerEffect::Bury]);
+        assert_eq!(game.apply(Action::Target { target: 1, effect: JokerEffect::Forge }), Err(ActionError::CannotDestroyItself));
+        assert_eq!(game.apply(Action::Target { target: 4, effect: JokerEffect::Forge }), Err(ActionError::NoRoomCard(4)));
+        assert_eq!(game.apply(Action::Flee), Err(ActionError::Unavailable(Action::Flee)));
+
+        game.apply(Action::Cancel).unwrap();
+        assert_eq!(game.state, GameState::Floor);
+        assert_eq!(game.room.len(), 3);
+
+        game.apply(Action::Use(1)).unwrap();
+        game.apply(Action::Target { target: 2, effect: JokerEffect::Forge }).unwrap();
+        assert_eq!(game.state, GameState::Floor);
+        assert_eq!(game.weapon_damage, 4);
+        assert_eq!(game.room[0], card(Suit::Hearts, Rank::Four));
+    }
+
+    #[test]
+    fn black_joker_forges_or_buries() {
+        let room = vec![Card::joker(JokerColor::Black), card(Suit::Clubs, Rank::Nine), card(Suit::Hearts, Rank::Four)];
+        let mut game = floor(room.clone(), filler());
+        game.weapon_damage = 8;
+        game.weapon_durability = 6;
+
+        let events = game.apply(Action::UseJoker { slot: 1, target: 2, effect: JokerEffect::Forge }).unwrap();
+        assert_eq!(events[0], GameEvent::Forged { card: card(Suit::Clubs, Rank::Nine), damage: 10 });
+        assert_eq!(game.weapon_durability, 6);
+
+        let mut game = floor(room, filler());
+        let events = game.apply(Action::UseJoker { slot: 1, target: 2, effect: JokerEffect::Bury }).unwrap();
+        assert_eq!(events[0], GameEvent::Buried { card: card(Suit::Clubs, Rank::Nine) });
+        assert_eq!(game.dungeon.last(), Some(&card(Suit::Clubs, Rank::Nine)));
+        assert_eq!(game.dungeon_discard, vec![Card::joker(JokerColor::Black)]);
+    }
+
+    #[test]
+    fn forging_large_joker_values_saturates() {
+        let mut game = floor(vec![Card::joker(JokerColor::Black), Card::joker(JokerColor::Red)], filler());
+        game.rules.red_joker_value = 500;
+        game.weapon_damage = 8;
+
+        game.apply(Action::UseJoker { slot: 1, target: 2, effect: JokerEffect::Forge }).unwrap();
+        assert_eq!(game.weapon_damage, MAX_FORGED_DAMAGE);
+    }
+
+    #[test]
+    fn joker_targets_only_for_jokers() {
+        let game = floor(vec![card(Suit::Clubs, Rank::Eight), card(Suit::Hearts, Rank::Four)], filler());
+        assert_eq!(game.joker_targets(1), Vec::<usize>::new());
+        assert_eq!(game.joker_targets(5), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn use_empty_slot() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Eight)], filler());
+        assert_eq!(game.apply(Action::Use(0)), Err(ActionError::NoRoomCard(0)));
+        assert_eq!(game.apply(Action::Use(2)), Err(ActionError::NoRoomCard(2)));
+    }
+
+    #[test]
+    fn flee_moves_room_to_bottom() {
+        let room = vec![card(Suit::Clubs, Rank::Eight), card(Suit::Clubs, Rank::Nine), card(Suit::Spades, Rank::Eight), card(Suit::Spades, Rank::Nine)];
+        let dungeon = vec![card(Suit::Hearts, Rank::Four), card(Suit::Hearts, Rank::Five), card(Suit::Hearts, Rank::Six), card(Suit::Hearts, Rank::Seven)];
+        let mut game = floor(room.clone(), dungeon.clone());
+
+        let events = game.apply(Action::Flee).unwrap();
+        assert_eq!(events, vec![GameEvent::Fled, GameEvent::RoomRestocked]);
+        assert_eq!(game.room, dungeon);
+        assert_eq!(game.dungeon, room.into_iter().rev().collect::<Vec<Card>>());
+        assert!(game.fled);
+    }
+
+    #[test]
+    fn flee_restrictions() {
+        let room = vec![card(Suit::Clubs, Rank::Eight); 4];
+        let mut game = floor(room.clone(), filler());
+
+        game.apply(Action::Flee).unwrap();
+        assert_eq!(game.apply(Action::Flee), Err(ActionError::FledTwice));
+
+        game.apply(Action::Use(1)).unwrap();
+        assert!(!game.fled);
+        assert_eq!(game.apply(Action::Flee), Err(ActionError::RoomNotFull));
+    }
+
+    #[test]
+    fn room_restocks_at_one_card() {
+        let mut game = floor(vec![card(Suit::Hearts, Rank::Four), card(Suit::Hearts, Rank::Five), card(Suit::Clubs, Rank::Four)], filler());
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert!(!events.contains(&GameEvent::RoomRestocked));
+        assert_eq!(game.room.len(), 2);
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert!(events.contains(&GameEvent::RoomRestocked));
+        assert_eq!(game.room.len(), 4);
+        assert_eq!(game.dungeon.len(), 5);
+    }
+
+    #[test]
+    fn losing_at_zero_health() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Nine), card(Suit::Clubs, Rank::Five)], filler());
+        game.health = 9;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::Lost));
+        assert_eq!(game.state, GameState::Lost);
+        assert_eq!(game.apply(Action::Use(1)), Err(ActionError::Unavailable(Action::Use(1))));
+    }
+
+    #[test]
+    fn floor_complete_opens_shop() {
+        let shop = vec![card(Suit::Hearts, Rank::Jack), card(Suit::Hearts, Rank::Queen), card(Suit::Diamonds, Rank::Jack), card(Suit::Diamonds, Rank::Queen), Card::joker(JokerColor::Red)];
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Four), card(Suit::Hearts, Rank::Five)], vec![]);
+        game.shop = shop.clone();
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::FloorComplete));
+        assert_eq!(game.state, GameState::Shop);
+        assert_eq!(game.shop_stock, shop[..4].to_vec());
+        assert_eq!(game.shop, shop[4..].to_vec());
+    }
+
+    #[test]
+    fn hearts_left_do_not_block_completion() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Four), card(Suit::Hearts, Rank::Five), card(Suit::Diamonds, Rank::Five)], vec![]);
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.state, GameState::Shop);
+    }
+
+    #[test]
+    fn last_floor_wins() {
+        let mut game = floor(vec![card(Suit::Spades, Rank::King), card(Suit::Hearts, Rank::Five)], vec![]);
+        game.bosses.clear();
+        game.health = 18;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events, vec![
+            GameEvent::Fought { card: card(Suit::Spades, Rank::King), weapon: None, damage: 13, money: 0 },
+            GameEvent::FloorComplete,
+            GameEvent::Won,
+        ]);
+        assert_eq!(game.state, GameState::Won);
+    }
+
+    #[test]
+    fn buy_requires_money() {
+        let mut game = floor(vec![], filler());
+        game.state = GameState::Shop;
+        game.shop_stock = vec![card(Suit::Hearts, Rank::Jack), Card::joker(JokerColor::Red)];
+        game.money = 12;
+
+        assert_eq!(game.apply(Action::Buy(2)), Err(ActionError::CannotAfford));
+        assert_eq!(game.apply(Action::Buy(3)), Err(ActionError::NoShopCard(3)));
+
+        let events = game.apply(Action::Buy(1)).unwrap();
+        assert_eq!(events, vec![GameEvent::Purchased { card: card(Suit::Hearts, Rank::Jack), price: 11 }]);
+        assert_eq!(game.money, 1);
+        assert_eq!(game.dungeon.last(), Some(&card(Suit::Hearts, Rank::Jack)));
+        assert_eq!(game.shop_stock, vec![Card::joker(JokerColor::Red)]);
+    }
+
+    #[test]
+    fn continue_adds_bosses_and_starts_floor() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Four), card(Suit::Hearts, Rank::Five)], vec![]);
+        game.bosses = vec![card(Suit::Clubs, Rank::Ten), card(Suit::Spades, Rank::Ten), card(Suit::Clubs, Rank::Jack)];
+        game.shop = vec![Card::joker(JokerColor::Red)];
+        game.weapon_damage = 5;
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.state, GameState::Shop);
+        game.health = 2;
+
+        let events = game.apply(Action::Continue).unwrap();
+        assert_eq!(events, vec![
+            GameEvent::ShopRestocked,
+            GameEvent::BossesAdded(vec![card(Suit::Clubs, Rank::Ten), card(Suit::Spades, Rank::Ten)]),
+        ]);
+        assert_eq!(game.state, GameState::Floor);
+        assert_eq!(game.bosses, vec![card(Suit::Clubs, Rank::Jack)]);
+        assert_eq!(game.health, 12);
+        assert_eq!(game.weapon_damage, 0);
+        assert_eq!(game.shop, vec![Card::joker(JokerColor::Red)]);
+        assert!(game.shop_stock.is_empty());
+        assert_eq!(game.room.len() + game.dungeon.len(), 4);
+        assert!(game.dungeon_discard.is_empty());
+    }
+
+    #[test]
+    fn undo_restores_snapshot() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Nine), card(Suit::Diamonds, Rank::Five), card(Suit::Clubs, Rank::Four), card(Suit::Clubs, Rank::Six)], filler());
+        game.apply(Action::Flee).unwrap();
+        let before = save::to_string(&game);
+
+        game.apply(Action::Use(1)).unwrap();
+        assert!(!game.fled);
+        assert_eq!(game.apply(Action::Undo).unwrap(), vec![GameEvent::Undone]);
+        assert!(game.fled);
+        assert!(game.undo_used);
+        assert_eq!(game.apply(Action::Undo).unwrap(), vec![GameEvent::Undone]);
+        assert_eq!(game.apply(Action::Undo), Err(ActionError::NothingToUndo));
+
+        game.apply(Action::Flee).unwrap();
+        game.undo_used = false;
+        assert_eq!(save::to_string(&game), before);
+    }
+
+    #[test]
+    fn hardcore_disables_undo() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Nine), card(Suit::Clubs, Rank::Four)], filler());
+        game.undo_depth = 0;
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.apply(Action::Undo), Err(ActionError::UndoDisabled));
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let mut a = Game::with_seed(42);
+        let mut b = Game::with_seed(42);
+        for action in [Action::Flee, Action::Use(1), Action::Use(1), Action::Use(1)] {
+            assert_eq!(a.apply(action), b.apply(action));
+        }
+        assert_eq!(save::to_string(&a), save::to_string(&b));
+    }
+
+    #[test]
+    fn rules_shape_the_game() {
+        let rules = Ruleset { max_health: 20, starting_money: 0, room_size: 5, min_dungeon_rank: 2, bosses_per_floor: 3, ..Ruleset::default() };
+        let mut game = Game::with_rules(7, rules);
+        assert_eq!(game.health, 20);
+        assert_eq!(game.money, 0);
+        assert_eq!(game.room.len(), 5);
+        assert_eq!(game.room.len() + game.dungeon.len(), 32);
+        assert!(game.legal_actions().contains(&Action::Flee));
+
+        game.apply(Action::Win).unwrap();
+        let events = game.apply(Action::Continue).unwrap();
+        assert!(events.contains(&GameEvent::BossesAdded(vec![card(Suit::Clubs, Rank::Ten), card(Suit::Spades, Rank::Ten), card(Suit::Clubs, Rank::Jack)])));
+        assert_eq!(game.rules, rules);
+    }
+
+    #[test]
+    fn king_of_spades_shatters_weapon() {
+        let mut game = floor(vec![card(Suit::Diamonds, Rank::Nine), card(Suit::Spades, Rank::King), card(Suit::Clubs, Rank::Five)], filler());
+
+        game.apply(Action::Use(1)).unwrap();
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[1], GameEvent::Shattered { boss: card(Suit::Spades, Rank::King) });
+        assert_eq!(game.health, 8);
+        assert_eq!(game.weapon_damage, 0);
+        assert!(game.weapon_stack.is_empty());
+        assert_eq!(game.dungeon_discard, vec![card(Suit::Diamonds, Rank::Nine), card(Suit::Spades, Rank::King)]);
+    }
+
+    #[test]
+    fn queen_of_clubs_prevents_fleeing() {
+        let room = vec![card(Suit::Clubs, Rank::Queen), card(Suit::Clubs, Rank::Eight), card(Suit::Clubs, Rank::Eight), card(Suit::Clubs, Rank::Eight)];
+        let mut game = floor(room, filler());
+        assert!(!game.legal_actions().contains(&Action::Flee));
+        assert_eq!(game.apply(Action::Flee), Err(ActionError::Anchored));
+    }
+
+    #[test]
+    fn queen_of_spades_curses_hearts() {
+        let mut game = floor(vec![card(Suit::Spades, Rank::Queen), card(Suit::Hearts, Rank::Five), card(Suit::Hearts, Rank::Four)], filler());
+        game.health = 5;
+        let events = game.apply(Action::Use(2)).unwrap();
+        assert_eq!(events[0], GameEvent::Cursed { boss: card(Suit::Spades, Rank::Queen), card: card(Suit::Hearts, Rank::Five) });
+        assert_eq!(game.health, 5);
+    }
+
+    #[test]
+    fn armored_venom_and_thief() {
+        let mut game = floor(vec![card(Suit::Diamonds, Rank::Eight), card(Suit::Clubs, Rank::Ten), card(Suit::Clubs, Rank::King), card(Suit::Spades, Rank::Ten)], filler());
+        game.health = 40;
+        game.money = 7;
+
+        game.apply(Action::Use(1)).unwrap();
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Clubs, Rank::Ten), weapon: Some(8), damage: 6, money: 0 });
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[1], GameEvent::Robbed { boss: card(Suit::Clubs, Rank::King), money: 4 });
+        assert_eq!(game.money, 3);
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Spades, Rank::Ten), weapon: None, damage: 12, money: 0 });
+    }
+
+    #[test]
+    fn jack_summons_discarded_monster() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Five), card(Suit::Hearts, Rank::Four)], vec![card(Suit::Spades, Rank::Jack), card(Suit::Hearts, Rank::Two)]);
+        game.dungeon_discard = vec![card(Suit::Clubs, Rank::Six), card(Suit::Hearts, Rank::Three)];
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert!(events.contains(&GameEvent::Summoned { boss: card(Suit::Spades, Rank::Jack), card: card(Suit::Clubs, Rank::Five) }));
+        assert_eq!(game.dungeon.last(), Some(&card(Suit::Clubs, Rank::Five)));
+    }
+
+    #[test]
+    fn jack_summons_monster_slain_with_weapon() {
+        let mut game = floor(vec![card(Suit::Clubs, Rank::Five), card(Suit::Hearts, Rank::Four)], vec![card(Suit::Spades, Rank::Jack), card(Suit::Hearts, Rank::Two)]);
+        game.weapon_damage = 9;
+
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert!(game.weapon_stack.is_empty());
+        assert!(events.contains(&GameEvent::Summoned { boss: card(Suit::Spades, Rank::Jack), card: card(Suit::Clubs, Rank::Five) }));
+    }
+
+    #[test]
+    fn jack_in_opening_room_summons() {
+        let mut game = floor(vec![], vec![card(Suit::Spades, Rank::Jack), card(Suit::Hearts, Rank::Two)]);
+        game.dungeon_discard = vec![card(Suit::Clubs, Rank::Six), card(Suit::Hearts, Rank::Three)];
+
+        // the same deal that opens every floor
+        let mut events = vec![];
+        assert_eq!(game.restock_room(&mut events), 2);
+        assert_eq!(events, vec![GameEvent::Summoned { boss: card(Suit::Spades, Rank::Jack), card: card(Suit::Clubs, Rank::Six) }]);
+        assert_eq!(game.dungeon, vec![card(Suit::Clubs, Rank::Six)]);
+    }
+
+    fn classic(room: Vec<Card>, dungeon: Vec<Card>) -> Game {
+        let mut game = floor(room, dungeon);
+        game.rules = Ruleset::preset("classic").unwrap();
+        game.health = game.rules.max_health;
+        game
+    }
+
+    #[test]
+    fn flee_limit_per_floor() {
+        let room = vec![card(Suit::Clubs, Rank::Eight); 4];
+        let mut game = floor(room, vec![card(Suit::Hearts, Rank::Four); 8]);
+        game.rules.flees_per_floor = Some(1);
+
+        game.apply(Action::Flee).unwrap();
+        assert_eq!(game.flees_left(), Some(0));
+        for _i in 0..3 {
+            game.apply(Action::Use(1)).unwrap();
+        }
+        assert!(!game.fled);
+        assert!(!game.legal_actions().contains(&Action::Flee));
+        assert_eq!(game.apply(Action::Flee), Err(ActionError::NoFleesLeft));
+
+        game.apply(Action::Win).unwrap();
+        game.apply(Action::Continue).unwrap();
+        assert_eq!(game.flees_left(), Some(1));
+    }
+
+    #[test]
+    fn hard_difficulty() {
+        let rules = Ruleset::preset("hard").unwrap();
+        let game = Game::with_rules(1, rules);
+        assert_eq!(game.health, 10);
+        assert!(game.dungeon.iter().chain(&game.room).any(|card| matches!(card.card_type, CardType::Regular { rank: Rank::Two, .. })));
+
+        let mut sorted = game.bosses.clone();
+        sorted.sort();
+        assert_ne!(game.bosses, sorted);
+        assert_eq!(Game::with_rules(1, Ruleset::default()).bosses, sorted);
+    }
+
+    #[test]
+    fn classic_deck() {
+        let game = Game::with_rules(3, Ruleset::preset("classic").unwrap());
+        let cards: Vec<&Card> = game.room.iter().chain(&game.dungeon).collect();
+        assert_eq!(cards.len(), 44);
+        assert!(game.bosses.is_empty() && game.shop.is_empty());
+        assert!(cards.iter().all(|card| match card.card_type {
+            CardType::Regular { suit: Suit::Hearts | Suit::Diamonds, rank } => (Rank::Two..=Rank::Ten).contains(&rank),
+            CardType::Regular { .. } => true,
+            CardType::Joker { .. } => false,
+        }));
+    }
+
+    #[test]
+    fn classic_heals_once_per_room() {
+        let mut game = classic(vec![card(Suit::Hearts, Rank::Five), card(Suit::Hearts, Rank::Four), card(Suit::Clubs, Rank::Six)], filler());
+        game.health = 10;
+
+        game.apply(Action::Use(1)).unwrap();
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Wasted { card: card(Suit::Hearts, Rank::Four) });
+        assert_eq!(game.health, 15);
+    }
+
+    #[test]
+    fn classic_aces_high_and_weapon_stack() {
+        let mut game = classic(vec![card(Suit::Diamonds, Rank::Five), card(Suit::Spades, Rank::Ace), card(Suit::Clubs, Rank::Seven)], filler());
+
+        game.apply(Action::Use(1)).unwrap();
+        let events = game.apply(Action::Use(1)).unwrap();
+        assert_eq!(events[0], GameEvent::Fought { card: card(Suit::Spades, Rank::Ace), weapon: Some(5), damage: 9, money: 0 });
+        assert_eq!(game.weapon_durability, 14);
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.weapon_stack, vec![card(Suit::Spades, Rank::Ace), card(Suit::Clubs, Rank::Seven)]);
+        assert_eq!(game.dungeon_discard, vec![card(Suit::Diamonds, Rank::Five)]);
+    }
+
+    #[test]
+    fn classic_score() {
+        let mut game = classic(vec![card(Suit::Spades, Rank::Ace), card(Suit::Clubs, Rank::King), card(Suit::Hearts, Rank::Two)], vec![card(Suit::Clubs, Rank::Four)]);
+        assert_eq!(game.score(), None);
+        game.health = 3;
+        game.apply(Action::Use(2)).unwrap();
+        assert_eq!(game.state, GameState::Lost);
+        assert_eq!(game.score(), Some(-18));
+
+        let mut game = classic(vec![card(Suit::Hearts, Rank::Two)], vec![]);
+        game.bosses.clear();
+        game.apply(Action::Use(1)).unwrap();
+        assert_eq!(game.state, GameState::Won);
+        assert_eq!(game.score(), Some(20));
+    }
+}
@@ -0,0 +1,162 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::action::Action;
+use crate::game::{Game, GameState};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub action: Action,
+    // share of sampled dungeon orders in which the floor was cleared
+    pub survival: f64,
+    // average health left on cleared floors
+    pub health: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub best: Estimate,
+    pub estimates: Vec<Estimate>,
+}
+
+// rough value of a position at the end of a room
+fn score(game: &Game) -> f64 {
+    match game.state {
+        GameState::Lost => return -1000.0,
+        GameState::Shop | GameState::Won => return 1000.0 + game.health as f64,
+        GameState::Floor | GameState::Choosing { .. } => {}
+    }
+
+    let durability = game.weapon_durability.min(14) as f64 / 14.0;
+    let mut score = game.health as f64 + game.weapon_damage as f64 * durability;
+    // fleeing mostly pays off when the room would otherwise be fatal
+    if game.fled {
+        score -= 12.0;
+    }
+    score
+}
+
+// best score reachable before the room is restocked or left
+fn plan(game: &Game, dungeon_len: usize, depth: usize) -> f64 {
+    if depth == 0 || game.dungeon.len() != dungeon_len || !matches!(game.state, GameState::Floor | GameState::Choosing { .. }) {
+        return score(game)
+    }
+
+    let mut best = f64::MIN;
+    for action in game.legal_actions() {
+        let mut child = game.snapshot();
+        if child.apply(action).is_err() {
+            continue
+        }
+        let value = match action {
+            Action::Flee => score(&child),
+            _ => plan(&child, dungeon_len, depth - 1),
+        };
+        best = best.max(value);
+    }
+    best
+}
+
+// searches the rest of the current room, used to play out sampled floors
+pub fn rollout_action(game: &Game) -> Option<Action> {
+    let mut best: Option<(Action, f64)> = None;
+    for action in game.legal_actions() {
+        let mut child = game.snapshot();
+        if child.apply(action).is_err() {
+            continue
+        }
+        let value = match action {
+            Action::Flee => score(&child),
+            _ => plan(&child, game.dungeon.len(), 3),
+        };
+        if best.is_none_or(|(_, best)| value > best) {
+            best = Some((action, value));
+        }
+    }
+    best.map(|(action, _)| action)
+}
+
+// plays until the floor ends, returns whether it was cleared
+pub fn rollout(game: &mut Game) -> bool {
+    while matches!(game.state, GameState::Floor | GameState::Choosing { .. }) {
+        let Some(action) = rollout_action(game) else {
+            return false
+        };
+        if game.apply(action).is_err() {
+            return false
+        }
+    }
+    matches!(game.state, GameState::Shop | GameState::Won)
+}
+
+// samples dungeon orders the player can't see, so the hint never depends on the real one
+pub fn hint(game: &Game, samples: usize, seed: u64) -> Option<Hint> {
+    if !matches!(game.state, GameState::Floor | GameState::Choosing { .. }) || samples == 0 {
+        return None
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut base = game.snapshot();
+    base.undo_depth = 0;
+
+    let mut estimates = vec![];
+    for action in game.legal_actions() {
+        let mut cleared = 0;
+        let mut health = 0;
+        for _i in 0..samples {
+            let mut sample = base.snapshot();
+            sample.dungeon.shuffle(&mut rng);
+            if sample.apply(action).is_ok() && rollout(&mut sample) {
+                cleared += 1;
+                health += sample.health as u32;
+            }
+        }
+        estimates.push(Estimate {
+            action,
+            survival: cleared as f64 / samples as f64,
+            health: if cleared > 0 { health as f64 / cleared as f64 } else { 0.0 },
+        });
+    }
+
+    let best = estimates.iter()
+        .max_by(|a, b| (a.survival, a.health).partial_cmp(&(b.survival, b.health)).unwrap())?
+        .clone();
+    Some(Hint { best, estimates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::GameBuilder;
+    use crate::card::{Card, Rank, Suit};
+
+    #[test]
+    fn avoids_certain_death() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::new(Suit::Hearts, Rank::Eight), Card::new(Suit::Clubs, Rank::Nine), Card::new(Suit::Diamonds, Rank::Nine)])
+            .dungeon(vec![Card::new(Suit::Hearts, Rank::Four), Card::new(Suit::Hearts, Rank::Five), Card::new(Suit::Hearts, Rank::Six), Card::new(Suit::Clubs, Rank::Four)])
+            .health(9)
+            .build()
+            .unwrap();
+
+        let hint = hint(&game, 20, 1).unwrap();
+        assert_eq!(hint.estimates.len(), 5);
+        let fight = hint.estimates.iter().find(|e| e.action == Action::Use(1)).unwrap();
+        assert_eq!(fight.survival, 0.0);
+        assert!(hint.best.survival > 0.0);
+        assert_ne!(hint.best.action, Action::Use(1));
+    }
+
+    #[test]
+    fn same_seed_same_hint() {
+        let game = Game::with_seed(5);
+        assert_eq!(hint(&game, 10, 3), hint(&game, 10, 3));
+    }
+
+    #[test]
+    fn no_hint_outside_floor() {
+        let game = GameBuilder::new().state(GameState::Shop).build().unwrap();
+        assert_eq!(hint(&game, 10, 0), None);
+    }
+}
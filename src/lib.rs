@@ -0,0 +1,29 @@
+pub mod action;
+pub mod boss;
+pub mod builder;
+pub mod card;
+pub mod event;
+pub mod game;
+pub mod hint;
+pub mod player;
+pub mod replay;
+pub mod rules;
+pub mod save;
+pub mod simulate;
+pub mod solve;
+pub mod view;
+
+pub use action::{Action, ActionError, JokerEffect};
+pub use boss::Ability;
+pub use builder::{BuildError, GameBuilder};
+pub use card::{Card, CardType, JokerColor, Rank, Suit};
+pub use event::GameEvent;
+pub use game::{Game, GameState, DEFAULT_UNDO_DEPTH};
+pub use hint::{hint, Estimate, Hint};
+pub use player::{Player, PLAYERS};
+pub use replay::{Replay, ReplayError};
+pub use rules::{Difficulty, Mode, Ruleset, RulesError, DIFFICULTIES, PRESETS};
+pub use save::SaveError;
+pub use simulate::{simulate, RunStats, Summary};
+pub use solve::{solve, Solution, Solver};
+pub use view::PlayerView;
@@ -1,13 +1,20 @@
 use colored::ColoredString;
-use rand::seq::SliceRandom;
+use colored::Colorize;
+use serde::Deserialize;
+use dungeoncards::{hint, player, save, simulate, solve, Ability, Action, JokerEffect, Mode, Replay, Ruleset, Summary, Card, CardType, Game, GameEvent, GameState, JokerColor, Rank, Suit, DIFFICULTIES, PLAYERS, PRESETS};
 use std::io;
-use std::cmp;
-use std::u8;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
-use std::cmp::Ordering;
-use colored::Colorize;
 
+mod theme;
+#[cfg(feature = "tui")]
+mod tui;
+
+use theme::Theme;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 enum TextType {
     Notification,
     Bad,
@@ -29,107 +36,158 @@ enum TextType {
 
 impl TextType {
     fn stylize(self, text: &str) -> ColoredString {
-        match self {
-            Self::Notification => text.italic().truecolor(110, 110, 110),
-            Self::Bad => text.red(),
-            Self::Ok => text.yellow(),
-            Self::Good => text.green(),
-            Self::Money => text.truecolor(200, 150, 25),
-            Self::Hearts => text.truecolor(200, 0, 0),
-            Self::Diamonds => text.truecolor(200, 75, 25),
-            Self::Clubs => text.truecolor(25, 100, 25),
-            Self::Spades => text.truecolor(25, 25, 100),
-            Self::BlackJoker => text.truecolor(150, 25, 150),
-            Self::RedJoker => text.truecolor(255, 25, 75),
-            Self::Dungeon => text.bold().truecolor(0, 50, 75),
-            Self::Shop => text.bold().truecolor(100, 50, 0),
-            Self::Lost => text.bold().red(),
-            Self::Won => text.bold().green(),
-            Self::Command => text.truecolor(110, 110, 110),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Suit {
-    Hearts,
-    Diamonds,
-    Clubs,
-    Spades,
+        output().theme.style(self).apply(text)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, EnumIter, Eq)]
-enum Rank {
-    Ace = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
-    Ten = 10,
-    Jack = 11,
-    Queen = 12,
-    King = 13,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Palette {
+    Plain,
+    Ansi16,
+    Ansi256,
+    TrueColor,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum JokerColor {
-    Red,
-    Black,
+impl Palette {
+    fn detect() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self::from_env(no_color, std::env::var("COLORTERM").ok().as_deref(), std::env::var("TERM").ok().as_deref())
+    }
+
+    fn from_env(no_color: bool, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color {
+            return Self::Plain
+        }
+        if colorterm.is_some_and(|value| value == "truecolor" || value == "24bit") {
+            return Self::TrueColor
+        }
+        if term.is_some_and(|value| value.contains("256color")) {
+            return Self::Ansi256
+        }
+        Self::Ansi16
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum CardType {
-    Regular {
-        suit: Suit,
-        rank: Rank,
-    },
-    Joker {
-        color: JokerColor,
-    },
+struct Output {
+    palette: Palette,
+    ascii: bool,
+    accessible: bool,
+    theme: Theme,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Card {
-    card_type: CardType,
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+fn output() -> &'static Output {
+    OUTPUT.get_or_init(|| Output { palette: Palette::detect(), ascii: false, accessible: false, theme: Theme::default() })
 }
 
-impl Card {
-    fn get_value(&self) -> u32 {
-        match self.card_type {
-            CardType::Regular { rank, .. } => {
-                rank as u32
-            }
-            CardType::Joker { .. } => {
-                15
-            }
+// nearest entry of the 6x6x6 cube or the grayscale ramp
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            _ => 232 + (r - 8) / 10,
         }
     }
+    let level = |c: u8| if c < 48 { 0 } else if c < 115 { 1 } else { (c - 35) / 40 };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+// colored already falls back to the 16 basic colors without COLORTERM
+fn rgb(text: ColoredString, r: u8, g: u8, b: u8) -> ColoredString {
+    match output().palette {
+        Palette::Plain => text,
+        _ if !colored::control::SHOULD_COLORIZE.should_colorize() => text,
+        Palette::Ansi256 => ColoredString::from(format!("\x1b[38;5;{}m{}\x1b[0m", ansi256(r, g, b), text)),
+        Palette::Ansi16 | Palette::TrueColor => text.truecolor(r, g, b),
+    }
+}
+
+trait CardDisplay {
+    fn display(&self) -> ColoredString;
+}
+
+fn rank_symbol(rank: Rank) -> String {
+    match rank {
+        Rank::Ace => String::from("A"),
+        Rank::Jack => String::from("J"),
+        Rank::Queen => String::from("Q"),
+        Rank::King => String::from("K"),
+        _ => format!("{}", rank as u8),
+    }
+}
+
+fn suit_symbol(suit: Suit, ascii: bool) -> &'static str {
+    match (suit, ascii) {
+        (Suit::Hearts, false) => "♥",
+        (Suit::Diamonds, false) => "♦",
+        (Suit::Clubs, false) => "♣",
+        (Suit::Spades, false) => "♠",
+        (Suit::Hearts, true) => "H",
+        (Suit::Diamonds, true) => "D",
+        (Suit::Clubs, true) => "C",
+        (Suit::Spades, true) => "S",
+    }
+}
+
+// ascii names like 10H, KS and JoR, used where colors are unavailable
+fn card_text(card: &Card) -> String {
+    match card.card_type {
+        CardType::Regular { suit, rank } => format!("{}{}", rank_symbol(rank), suit_symbol(suit, true)),
+        CardType::Joker { color: JokerColor::Red } => String::from("JoR"),
+        CardType::Joker { color: JokerColor::Black } => String::from("JoB"),
+    }
+}
+
+fn weapon_text(damage: u8) -> String {
+    if output().accessible {
+        format!("a {} damage weapon", damage)
+    } else {
+        format!("{}{}", damage, suit_symbol(Suit::Diamonds, output().ascii))
+    }
+}
+
+// spelled out for screen readers, e.g. Seven of Clubs or Red Joker
+fn card_name(card: &Card) -> String {
+    match card.card_type {
+        CardType::Regular { suit, rank } => format!("{:?} of {:?}", rank, suit),
+        CardType::Joker { color } => format!("{:?} Joker", color),
+    }
+}
+
+// the card's name and what using it does
+fn describe(card: &Card, rules: &Ruleset) -> ColoredString {
+    let effect = match card.card_type {
+        CardType::Regular { suit: Suit::Clubs | Suit::Spades, rank } => format!("monster, {} damage", rules.rank_value(rank)),
+        CardType::Regular { suit: Suit::Hearts, rank } if rank < Rank::Jack => format!("potion, heals {}", rank as u8),
+        CardType::Regular { suit: Suit::Hearts, rank } => format!("potion, full heal plus {}", (rank as u8 - Rank::Ten as u8) * 2),
+        CardType::Regular { suit: Suit::Diamonds, rank } if rank < Rank::Jack => format!("weapon, {} damage", rank as u8),
+        CardType::Regular { suit: Suit::Diamonds, rank } => format!("repairs weapon by {}", (rank as u8 - Rank::Ten as u8) * 2),
+        CardType::Joker { color: JokerColor::Red } => String::from("joker, destroys another room card and heals half its value"),
+        CardType::Joker { color: JokerColor::Black } => String::from("joker, forges another room card into your weapon or buries it in the dungeon"),
+    };
+    let mut text = format!("{}, {}", card_name(card), effect);
+    if let Some(ability) = Ability::of(card, rules) {
+        text.push_str(&format!(", {}", ability.description()));
+    }
+    match card.card_type {
+        CardType::Regular { suit: Suit::Hearts, .. } => TextType::Hearts.stylize(&text),
+        CardType::Regular { suit: Suit::Diamonds, .. } => TextType::Diamonds.stylize(&text),
+        CardType::Regular { suit: Suit::Clubs, .. } => TextType::Clubs.stylize(&text),
+        CardType::Regular { suit: Suit::Spades, .. } => TextType::Spades.stylize(&text),
+        CardType::Joker { color: JokerColor::Black } => TextType::BlackJoker.stylize(&text),
+        CardType::Joker { color: JokerColor::Red } => TextType::RedJoker.stylize(&text),
+    }
+}
 
+impl CardDisplay for Card {
     fn display(&self) -> ColoredString {
         let text = match self.card_type {
-            CardType::Regular { suit, rank } => {
-                let suit_symbol = match suit {
-                    Suit::Hearts => String::from("♥"),
-                    Suit::Diamonds => String::from("♦"),
-                    Suit::Clubs => String::from("♣"),
-                    Suit::Spades => String::from("♠"),
-                };
-                let rank_symbol = match rank {
-                    Rank::Ace => String::from("A"),
-                    Rank::Jack => String::from("J"),
-                    Rank::Queen => String::from("Q"),
-                    Rank::King => String::from("K"),
-                    _ => format!("{}", rank as u8),
-                };
-                format!("{}{}", rank_symbol, suit_symbol)
-            },
-            CardType::Joker{ .. } => {
-                String::from("Jo")
-            }
+            _ if output().accessible => card_name(self),
+            _ if output().ascii => card_text(self),
+            CardType::Regular { suit, rank } => format!("{}{}", rank_symbol(rank), suit_symbol(suit, false)),
+            CardType::Joker{ .. } => String::from("Jo"),
         };
 
         match self.card_type {
@@ -151,438 +209,871 @@ impl Card {
     }
 }
 
-impl Ord for Card {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.card_type {
-            CardType::Regular { suit, rank } => match other.card_type {
-                CardType::Regular { suit: other_suit, rank: other_rank } => {
-                    rank.cmp(&other_rank).then_with(|| suit.cmp(&other_suit))
-                }
-                CardType::Joker { .. } => Ordering::Greater
-            },
-            CardType::Joker { color } => match other.card_type {
-                CardType::Regular { .. } => Ordering::Less,
-                CardType::Joker { color: other_color } => color.cmp(&other_color)
-            }
+fn display_run(game: &Game) {
+    let mut text = format!("Seed: {}, Difficulty: {:?}", game.seed, game.rules.difficulty);
+    if game.undo_used {
+        text.push_str(" (undo used)");
+    }
+    if game.rules.mode == Mode::Classic {
+        if let Some(score) = game.score() {
+            text.push_str(&format!(", Score: {}", score));
         }
     }
+    println!("{}", TextType::Notification.stylize(text.as_str()));
 }
 
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+fn display_floor(game: &Game) {
+    println!("{} card(s) left in Dungeon", game.dungeon.len());
+    let health_text = format!("{}/{} HP", game.health, game.rules.max_health);
+    // thirds of the maximum, 0-4, 5-8 and 9-12 by default
+    let third = game.health as u32 * 3;
+    let health_color = if third <= game.rules.max_health as u32 {
+        TextType::Bad
+    } else if third <= game.rules.max_health as u32 * 2 {
+        TextType::Ok
+    } else {
+        TextType::Good
+    };
+    let money_text = format!("${}", game.money);
+    println!("{}, {}", health_color.stylize(health_text.as_str()), TextType::Money.stylize(money_text.as_str()));
+    print!("Room:");
+    for card in &game.room {
+        print!(" {}", card.display());
+    }
+    println!();
+    for card in &game.room {
+        if let Some(ability) = game.ability(card) {
+            println!("  {} {}: {}", card.display(), TextType::Bad.stylize(ability.to_string().as_str()), ability.description());
+        }
+    }
+    if game.weapon_damage > 0 {
+        print!("Weapon: {}", TextType::Diamonds.stylize(weapon_text(game.weapon_damage).as_str()));
+        if game.weapon_durability < u8::MAX {
+            print!(" ({} durability)", game.weapon_durability);
+        }
+        if !game.weapon_stack.is_empty() {
+            let slain: Vec<String> = game.weapon_stack.iter().map(|card| card.display().to_string()).collect();
+            print!(", slain: {}", slain.join(" "));
+        }
+        println!();
+    }
+    if let Some(flees) = game.flees_left() {
+        println!("Flees left: {}", flees);
     }
 }
 
-struct Game {
-    dungeon: Vec<Card>,
-    dungeon_discard: Vec<Card>,
-    room: Vec<Card>,
-    bosses: Vec<Card>,
-    shop: Vec<Card>,
-    shop_stock: Vec<Card>,
-    shop_discard: Vec<Card>,
-    health: u8,
-    money: u32,
-    weapon_damage: u8,
-    weapon_durability: u8,
-    fled: bool,
-    state: GameState,
+fn heading(text_type: TextType, title: &str) {
+    if output().accessible {
+        println!("{}", text_type.stylize(title));
+    } else {
+        println!("{}", text_type.stylize(format!("===== {} =====", title).as_str()));
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum GameState {
-    Floor,
-    Shop,
-    Lost,
-    Won,
+// read out in place of the glyph lines
+fn display_summary(game: &Game) {
+    let weapon = match (game.weapon_damage, game.weapon_durability) {
+        (0, _) => String::from("no weapon"),
+        (damage, u8::MAX) => format!("a fresh {} damage weapon", damage),
+        (damage, durability) => format!("a {} damage weapon for monsters below {}", damage, durability),
+    };
+    println!("{} of {} health, {} dollars, {}, {} cards left in the dungeon.", game.health, game.rules.max_health, game.money, weapon, game.dungeon.len());
+    if let Some(flees) = game.flees_left() {
+        println!("{} flees left on this floor.", flees);
+    }
 }
 
-impl Game {
-    fn new() -> Self {
-        let mut deck: Vec<Card> = Self::create_deck();
-        deck.shuffle(&mut rand::rng());
-
-        let mut dungeon = vec![];
-        let mut bosses = vec![];
-        let mut shop = vec![];
-        for card in deck {
-            match card.card_type {
-                CardType::Regular { suit, rank } => {
-                    match rank as u8 {
-                        4..=9 => {
-                            dungeon.push(card);
-                        },
-                        10..=13 => {
-                            match suit {
-                                Suit::Hearts | Suit::Diamonds => shop.push(card),
-                                Suit::Clubs | Suit::Spades => bosses.push(card),
-                            }
-                        },
-                        0_u8..=3_u8 | 14_u8..=u8::MAX => { }
+fn display(game: &Game, tracking: bool) {
+    match game.state {
+        GameState::Floor => {
+            heading(TextType::Dungeon, "Dungeon");
+            if output().accessible {
+                display_summary(game);
+                println!("Room:");
+                for (i, card) in game.room.iter().enumerate() {
+                    println!("{}: {}", i + 1, describe(card, &game.rules));
+                }
+            } else {
+                display_floor(game);
+            }
+            if tracking {
+                display_tracker(game);
+            }
+
+            println!("{}", TextType::Command.stylize(format!("Commands: use [card 1-{}], flee, hint, track, undo, save [name], load [name], quit", game.rules.room_size).as_str()));
+        }
+        GameState::Choosing { joker } if output().accessible => {
+            println!("Choose a card for the joker in slot {}:", joker);
+            for target in game.joker_targets(joker) {
+                println!("{}: {}", target, describe(&game.room[target - 1], &game.rules));
+            }
+            display_effects(game, joker);
+        }
+        GameState::Choosing { joker } => {
+            print!("Room:");
+            for (i, card) in game.room.iter().enumerate() {
+                if i + 1 == joker {
+                    print!(" [{}]", card.display());
+                } else {
+                    print!(" {}", card.display());
+                }
+            }
+            println!();
+            let targets: Vec<String> = game.joker_targets(joker).iter().map(|idx| idx.to_string()).collect();
+            println!("Choose a card for the joker ({}):", targets.join(", "));
+            display_effects(game, joker);
+        }
+        GameState::Lost => {
+            heading(TextType::Lost, "Game over");
+            display_run(game);
+            println!("{}", TextType::Command.stylize("Commands: retry, undo, load [name], quit"));
+        }
+        GameState::Shop => {
+            heading(TextType::Shop, "Shop");
+            if output().accessible {
+                println!("{} dollars.", game.money);
+                for (i, card) in game.shop_stock.iter().enumerate() {
+                    println!("{}: {}, costs {} dollars", i + 1, describe(card, &game.rules), game.rules.value(card));
+                }
+            } else {
+                println!("{}", TextType::Money.stylize(format!("${}", game.money).as_str()));
+                if !game.shop_stock.is_empty() {
+                    print!("On sale:");
+                    for card in &game.shop_stock {
+                        print!(" {}-{}", card.display(), TextType::Money.stylize(format!("${}", game.rules.value(card)).as_str()));
                     }
-                },
-                CardType::Joker { .. } => {
-                    shop.push(card);
+                    println!();
                 }
             }
+
+            println!("{}", TextType::Command.stylize(format!("Commands: buy [card 1-{}], continue, undo, save [name], load [name], quit", game.rules.shop_size).as_str()));
+        }
+        GameState::Won => {
+            heading(TextType::Won, "You win!");
+            display_run(game);
+            println!("{}", TextType::Command.stylize("Commands: retry, undo, load [name], quit"));
         }
-        bosses.sort();
+    }
+    print!("> ");
+}
+
+// the options of the chosen joker and how to pick one
+fn display_effects(game: &Game, joker: usize) {
+    let effects = game.joker_effects(joker);
+    for effect in effects {
+        println!("  {}: {}", TextType::Good.stylize(effect.name()), effect.description());
+    }
+    if effects.len() > 1 {
+        let names: Vec<&str> = effects.iter().map(|effect| effect.name()).collect();
+        println!("{}", TextType::Command.stylize(format!("Commands: [card 1-{}] [{}], cancel", game.rules.room_size, names.join("|")).as_str()));
+    } else {
+        println!("{}", TextType::Command.stylize(format!("Commands: [card 1-{}], cancel", game.rules.room_size).as_str()));
+    }
+}
 
-        Game {
-            dungeon,
-            dungeon_discard: vec![],
-            room: vec![],
-            bosses,
-            shop,
-            shop_stock: vec![],
-            shop_discard: vec![],
-            health: 12, 
-            money: 5, 
-            weapon_damage: 0,
-            weapon_durability: u8::MAX, 
-            fled: false, 
-            state: GameState::Floor,
+// what card counting tells about the rest of the dungeon
+fn display_tracker(game: &Game) {
+    let remaining = game.view(true).remaining.unwrap_or_default();
+    let count = |suits: &[Suit], rank: Rank| {
+        remaining.iter().filter(|card| matches!(card.card_type, CardType::Regular { suit, rank: r } if suits.contains(&suit) && r == rank)).count()
+    };
+    let ranks: Vec<Rank> = Rank::iter().filter(|rank| count(&[Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades], *rank) > 0).collect();
+
+    println!("{}", TextType::Notification.stylize("----- Tracker -----"));
+    let header: Vec<String> = ranks.iter().map(|rank| format!("{:>3}", rank_symbol(*rank))).collect();
+    println!("{:<9}{}", "Rank", header.join(""));
+    let rows = [("Monsters", vec![Suit::Clubs, Suit::Spades], TextType::Clubs), ("Hearts", vec![Suit::Hearts], TextType::Hearts), ("Diamonds", vec![Suit::Diamonds], TextType::Diamonds)];
+    for (name, suits, text_type) in rows {
+        let counts: Vec<String> = ranks.iter().map(|rank| format!("{:>3}", count(&suits, *rank))).collect();
+        println!("{}{}", text_type.stylize(format!("{:<9}", name).as_str()), counts.join(""));
+    }
+    let jokers = remaining.iter().filter(|card| matches!(card.card_type, CardType::Joker { .. })).count();
+    if jokers > 0 {
+        println!("Jokers: {}", jokers);
+    }
+
+    let damage: u32 = remaining.iter()
+        .filter_map(|card| match card.card_type {
+            CardType::Regular { suit: Suit::Clubs | Suit::Spades, rank } => Some(game.rules.rank_value(rank) as u32),
+            _ => None,
+        })
+        .sum();
+    println!("Monster damage left: {}", TextType::Bad.stylize(damage.to_string().as_str()));
+    if !game.bosses.is_empty() {
+        // a shuffled queue is only shown sorted, so its order stays hidden
+        let mut bosses = game.bosses.clone();
+        if game.rules.shuffle_bosses {
+            bosses.sort();
         }
+        let bosses: Vec<String> = bosses.iter().map(|card| card.display().to_string()).collect();
+        println!("Bosses queued: {}", bosses.join(" "));
     }
+}
 
-    fn start_floor(&mut self) {
-        self.health = 12;
-        self.weapon_damage = 0;
-        self.weapon_durability = u8::MAX;
+fn health_change(amount: i64) -> String {
+    match (output().accessible, amount < 0) {
+        (true, true) => format!("lost {} health", -amount),
+        (true, false) => format!("gained {} health", amount),
+        (false, true) => format!("-{} HP", -amount),
+        (false, false) => format!("+{} HP", amount),
+    }
+}
 
-        self.dungeon.append(&mut self.room);
-        self.dungeon.append(&mut self.dungeon_discard);
-        self.dungeon.shuffle(&mut rand::rng());
+fn money_change(amount: i64) -> String {
+    match (output().accessible, amount < 0) {
+        (true, true) => format!("spent {} dollars", -amount),
+        (true, false) => format!("gained {} dollars", amount),
+        (false, true) => format!("-${}", -amount),
+        (false, false) => format!("+${}", amount),
     }
+}
 
-    fn create_deck() -> Vec<Card> {
-        let mut deck = Vec::with_capacity(52);
-        for suit in [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades] {
-            for rank in Rank::iter() {
-                deck.push(Card {
-                    card_type: CardType::Regular { suit, rank },
-                });
+fn display_event(event: &GameEvent) {
+    match event {
+        GameEvent::Fought { card, weapon, damage, money } => {
+            print!("Fought {} ", card.display());
+            match weapon {
+                Some(weapon) => print!("using {}, ", TextType::Diamonds.stylize(weapon_text(*weapon).as_str())),
+                None => print!("barehanded, "),
+            }
+            if *money > 0 {
+                println!("{}", TextType::Money.stylize(money_change(*money as i64).as_str()));
+            } else {
+                println!("{}", TextType::Bad.stylize(health_change(-(*damage as i64)).as_str()));
             }
         }
-        deck.push(Card {
-            card_type: CardType::Joker { color: JokerColor::Black }
-        });
-        deck.push(Card {
-            card_type: CardType::Joker { color: JokerColor::Red }
-        });
-        deck
+        GameEvent::Healed { amount, full: false, .. } => {
+            println!("{}", TextType::Good.stylize(health_change(*amount as i64).as_str()));
+        }
+        GameEvent::Healed { amount, full: true, .. } => {
+            let text = if output().accessible {
+                format!("Full heal, {}", health_change(*amount as i64))
+            } else {
+                format!("Full heal + {} HP", amount)
+            };
+            println!("{}", TextType::Good.stylize(text.as_str()));
+        }
+        GameEvent::Wasted { card } => {
+            println!("{}", TextType::Bad.stylize(format!("Discarded {}, already healed in this room", card.display()).as_str()));
+        }
+        GameEvent::Cursed { boss, card } => {
+            println!("{}", TextType::Bad.stylize(format!("Discarded {}, {} cursed it", card.display(), boss.display()).as_str()));
+        }
+        GameEvent::Equipped { card } => {
+            println!("Equipped {}", card.display());
+        }
+        GameEvent::Repaired { amount, .. } => {
+            println!("{}", TextType::Good.stylize(format!("Repaired {} durability", amount).as_str()));
+        }
+        GameEvent::Destroyed { card } => println!("Destroyed {}", card.display()),
+        GameEvent::Forged { card, damage } => {
+            println!("Forged {} into a {} weapon", card.display(), TextType::Diamonds.stylize(weapon_text(*damage).as_str()));
+        }
+        GameEvent::Buried { card } => println!("Buried {} at the bottom of the dungeon", card.display()),
+        GameEvent::Fled => println!("{}", TextType::Bad.stylize("Fled from room!")),
+        GameEvent::RoomRestocked => println!("{}", TextType::Notification.stylize("Restocked room")),
+        GameEvent::FloorComplete => println!("{}", TextType::Good.stylize("Floor complete!")),
+        GameEvent::Lost => println!("{}", TextType::Bad.stylize("You lost")),
+        GameEvent::Won => {}
+        GameEvent::Purchased { card, price } => {
+            println!("{}, {} added to dungeon", TextType::Bad.stylize(money_change(-(*price as i64)).as_str()), card.display());
+        }
+        GameEvent::ShopRestocked => println!("{}", TextType::Notification.stylize("Shop restocked")),
+        GameEvent::BossesAdded(bosses) => {
+            let names: Vec<String> = bosses.iter().map(|card| card.display().to_string()).collect();
+            println!("{} added to dungeon", names.join(" & "));
+        }
+        GameEvent::Summoned { boss, card } => {
+            println!("{} summoned {} back into the dungeon", boss.display(), card.display());
+        }
+        GameEvent::Robbed { boss, money } => {
+            println!("{} stole {}", boss.display(), TextType::Bad.stylize(money_change(-(*money as i64)).as_str()));
+        }
+        GameEvent::Shattered { boss } => {
+            println!("{}", TextType::Bad.stylize(format!("{} shattered your weapon", boss.display()).as_str()));
+        }
+        GameEvent::Undone => println!("{}", TextType::Notification.stylize("Undid last action")),
     }
+}
 
-    fn refresh_room(&mut self, quiet: bool) {
-        // restock room
-        if self.room.len() <= 1 {
-            let amount_add = cmp::min(4 - self.room.len(), self.dungeon.len());
-            for _i in 0..amount_add {
-                self.room.push(self.dungeon.remove(0));
-            }
+// None once stdin is closed
+fn read_input() -> Option<String> {
+    io::Write::flush(&mut io::stdout()).unwrap();
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input).unwrap() {
+        0 => None,
+        _ => Some(input),
+    }
+}
 
-            if amount_add > 0 && self.state == GameState::Floor && !quiet {
-                println!("{}", TextType::Notification.stylize("Restocked room"));
-            }
-        }    
+// slots is how many cards the room or shop can hold
+fn parse_slot(text: &str, slots: usize) -> Option<usize> {
+    match text.parse::<usize>() {
+        Ok(idx) => Some(idx),
+        Err(_) => {
+            println!("{}", TextType::Bad.stylize(format!("Must enter a number between 1 and {}", slots).as_str()));
+            None
+        }
+    }
+}
 
-        // check if lost
-        if self.health <= 0 {
-            self.state = GameState::Lost;
-            println!("{}", TextType::Bad.stylize("You lost"));
-            return
+// a joker with a single effect needs no name for it
+fn parse_effect(game: &Game, joker: usize, name: Option<&&str>) -> Option<JokerEffect> {
+    let effects = game.joker_effects(joker);
+    let effect = match name {
+        Some(name) => JokerEffect::from_name(name),
+        None if effects.len() == 1 => Some(effects[0]),
+        None => None,
+    };
+    if effect.is_none() {
+        let names: Vec<&str> = effects.iter().map(|effect| effect.name()).collect();
+        println!("{}", TextType::Bad.stylize(format!("Must choose one of {}", names.join(", ")).as_str()));
+    }
+    effect
+}
+
+fn parse_action(game: &Game, parts: &[&str]) -> Option<Action> {
+    let (room, shop) = (game.rules.room_size, game.rules.shop_size);
+    match (&game.state, parts) {
+        (GameState::Floor, ["use", card]) => Some(Action::Use(parse_slot(card, room)?)),
+        // extra arguments on anything but a joker are a mistake, not a plain use
+        (GameState::Floor, ["use", card, target, effect @ ..])
+            if effect.len() <= 1 && card.parse().is_ok_and(|slot| !game.joker_effects(slot).is_empty()) => {
+            let slot = parse_slot(card, room)?;
+            Some(Action::UseJoker { slot, target: parse_slot(target, room)?, effect: parse_effect(game, slot, effect.first())? })
+        }
+        (GameState::Choosing { .. }, ["cancel"]) => Some(Action::Cancel),
+        (GameState::Choosing { joker }, [target, effect @ ..]) if effect.len() <= 1 => {
+            Some(Action::Target { target: parse_slot(target, room)?, effect: parse_effect(game, *joker, effect.first())? })
         }
+        (GameState::Floor, ["flee"]) => Some(Action::Flee),
+        (GameState::Floor, ["win"]) => Some(Action::Win), // debug
+        (GameState::Shop, ["buy", card]) => Some(Action::Buy(parse_slot(card, shop)?)),
+        (GameState::Shop, ["steal", card]) => Some(Action::Steal(parse_slot(card, shop)?)), // debug
+        (GameState::Shop, ["continue"]) => Some(Action::Continue),
+        (GameState::Lost | GameState::Won, ["retry"]) => Some(Action::Retry),
+        (_, ["undo"]) => Some(Action::Undo),
+        _ => {
+            println!("{}", TextType::Bad.stylize("Invalid command"));
+            None
+        }
+    }
+}
 
-        // check if won
-        if self.dungeon.is_empty() && !self.room.iter().any(|card| 
-            matches!(card.card_type, CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. })) {
-            
-            println!("{}", TextType::Good.stylize("Floor complete!"));
+// without --theme, the config directory's theme.toml is used if there is one
+fn load_theme(name: Option<&str>) -> Result<Theme, String> {
+    if let Some(name) = name {
+        return Theme::load(name)
+    }
+    match dirs::config_dir().map(|dir| dir.join("dungeoncards").join("theme.toml")) {
+        Some(path) if path.exists() => Theme::read(&path),
+        _ => Ok(Theme::default()),
+    }
+}
 
-            if self.bosses.is_empty() {
-                self.state = GameState::Won;
-            } else {
-                self.state = GameState::Shop;
-                for _i in 0..cmp::min(self.shop.len(), 4) {
-                    self.shop_stock.push(self.shop.remove(0));
-                }
-            }
+fn load_rules(name: Option<&str>) -> Result<Ruleset, String> {
+    let Some(name) = name else {
+        return Ok(Ruleset::default())
+    };
+    match Ruleset::preset(name) {
+        Some(rules) => Ok(rules),
+        None if Path::new(name).exists() => Ruleset::read(Path::new(name)).map_err(|e| e.to_string()),
+        None => Err(format!("Unknown rules: {} (expected {}, {} or a ruleset file)", name, PRESETS.join(", "), DIFFICULTIES.join(", "))),
+    }
+}
+
+const DEFAULT_SAVE: &str = "quicksave";
+
+fn save_path(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid save name: {}", name))
+    }
+    let dir = dirs::data_dir().ok_or("No data directory available")?;
+    Ok(dir.join("dungeoncards").join("saves").join(format!("{}.json", name)))
+}
+
+fn save_game(game: &Game, name: &str) -> Result<(), String> {
+    save::write(game, &save_path(name)?).map_err(|e| e.to_string())
+}
+
+fn load_game(name: &str) -> Result<Game, String> {
+    save::read(&save_path(name)?).map_err(|e| e.to_string())
+}
+
+fn command_text(action: &Action) -> String {
+    match action {
+        Action::Use(slot) => format!("use {}", slot),
+        Action::UseJoker { slot, target, effect } => format!("use {} {} {}", slot, target, effect.name()),
+        Action::Target { target, effect } => format!("{} {}", target, effect.name()),
+        Action::Cancel => String::from("cancel"),
+        Action::Flee => String::from("flee"),
+        Action::Buy(slot) => format!("buy {}", slot),
+        Action::Continue => String::from("continue"),
+        Action::Retry => String::from("retry"),
+        Action::Undo => String::from("undo"),
+        Action::Win => String::from("win"),
+        Action::Steal(slot) => format!("steal {}", slot),
+    }
+}
+
+fn write_replay(replay: &mut Replay, game: &Game, path: &Path) {
+    replay.finish(game);
+    match replay.write(path) {
+        Ok(()) => println!("{}", TextType::Notification.stylize(format!("Replay written to {}", path.display()).as_str())),
+        Err(e) => println!("{}", TextType::Bad.stylize(e.to_string().as_str())),
+    }
+}
+
+fn run_replay(path: &Path) -> Result<(), String> {
+    let replay = Replay::read(path).map_err(|e| e.to_string())?;
+    let mut game = replay.start();
+    for action in &replay.actions {
+        display(&game, false);
+        println!("{}", command_text(action));
+        let events = game.apply(*action).map_err(|e| format!("Replay diverged at \"{}\": {}", command_text(action), e))?;
+        for event in &events {
+            display_event(event);
         }
     }
+    display(&game, false);
+    println!();
 
-    fn display(&self) {
-        match self.state {
-            GameState::Floor => {
-                println!("{}", TextType::Dungeon.stylize("===== Dungeon ====="));
-                println!("{} card(s) left in Dungeon", self.dungeon.len());
-                let health_text = format!("{}/12 HP", self.health);
-                let health_color = match self.health {
-                    0..=4 => TextType::Bad,
-                    5..=8 => TextType::Ok,
-                    _ => TextType::Good,
-                };
-                let money_text = format!("${}", self.money);
-                println!("{}, {}", health_color.stylize(health_text.as_str()), TextType::Money.stylize(money_text.as_str()));
-                print!("Room:");
-                for card in &self.room {
-                    print!(" {}", card.display());
-                }
-                print!("\n");
-                if self.weapon_damage > 0 {
-                    print!("Weapon: {}", TextType::Diamonds.stylize(format!("{}♦", self.weapon_damage).as_str()));
-                    if self.weapon_durability < u8::MAX {
-                        print!(" ({} durability)", self.weapon_durability);
-                    }
-                    print!("\n");
-                }
+    replay.verify(&game).map_err(|e| e.to_string())?;
+    match replay.checksum {
+        Some(_) => println!("{}", TextType::Good.stylize("Replay verified")),
+        None => println!("{}", TextType::Notification.stylize("Replay finished (no final state recorded)")),
+    }
+    Ok(())
+}
+
+const HINT_SAMPLES: usize = 100;
 
-                println!("{}", TextType::Command.stylize("Commands: use [card 1-4], flee, quit"));
+fn show_hint(game: &Game) {
+    let Some(hint) = hint(game, HINT_SAMPLES, rand::random()) else {
+        println!("{}", TextType::Bad.stylize("No hint available"));
+        return
+    };
+    println!("Hint: {} ({:.0}% survival)", command_text(&hint.best.action), hint.best.survival * 100.0);
+    let estimates: Vec<String> = hint.estimates.iter()
+        .map(|estimate| format!("{} {:.0}%", command_text(&estimate.action), estimate.survival * 100.0))
+        .collect();
+    println!("{}", TextType::Notification.stylize(estimates.join(", ").as_str()));
+}
+
+fn run_solve(game: &Game) {
+    println!("{}", TextType::Notification.stylize(format!("Seed: {}", game.seed).as_str()));
+    print!("Room:");
+    for card in &game.room {
+        print!(" {}", card.display());
+    }
+    println!();
+
+    let solution = solve(game);
+    if !solution.clearable {
+        println!("{}", TextType::Bad.stylize("Floor cannot be cleared"));
+        return
+    }
+    println!("{}", TextType::Good.stylize("Floor can be cleared"));
+    println!("Best: {} HP, {}", solution.max_health, TextType::Money.stylize(format!("${}", solution.max_money).as_str()));
+    let commands: Vec<String> = solution.actions.iter().map(command_text).collect();
+    println!("Line: {}", commands.join(", "));
+}
+
+const SIMULATE_GAMES: usize = 1000;
+
+fn run_simulate(options: &Options, rules: Ruleset) {
+    let bot = options.bot.as_deref().unwrap_or("greedy");
+    let games = options.games.unwrap_or(SIMULATE_GAMES);
+    let threads = options.threads.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let Some(runs) = simulate(bot, rules, games, threads, seed) else {
+        return
+    };
+    let summary = Summary::new(&runs);
+
+    match options.format {
+        Format::Table => {
+            println!("{}", TextType::Dungeon.stylize("===== Simulation ====="));
+            println!("Bot: {}, {} game(s), seed {}, {:?} difficulty", bot, summary.games, seed, rules.difficulty);
+            println!("Win rate: {:.1}% ({}/{})", summary.win_rate() * 100.0, summary.wins, summary.games);
+            println!("Average floors cleared: {:.2}", summary.average_floors());
+            println!("{:<10}{:>8}", "Floors", "Runs");
+            for (floors, count) in summary.floors.iter().enumerate() {
+                println!("{:<10}{:>8}", floors, count);
             }
-            GameState::Lost => {
-                println!("{}", TextType::Lost.stylize("===== Game over ====="));
-                println!("{}", TextType::Command.stylize("Commands: retry, quit"));
+            println!("{:<10}{:>8}", "Death", "Runs");
+            for (card, count) in &summary.deaths {
+                println!("{:<10}{:>8}", card_text(card), count);
             }
-            GameState::Shop => {
-                println!("{}", TextType::Shop.stylize("===== Shop ====="));
-                println!("{}", TextType::Money.stylize(format!("${}", self.money).as_str()));
-                if !self.shop_stock.is_empty() {
-                    print!("On sale:");
-                    for i in 0..cmp::min(self.shop_stock.len(), 4) {
-                        let card = &self.shop_stock[i];
-                        print!(" {}-{}", card.display(), TextType::Money.stylize(format!("${}", card.get_value()).as_str()));
-                    }
-                    print!("\n");
-                }
-                
-                println!("{}", TextType::Command.stylize("Commands: buy [card 1-4], continue, quit"));
+            println!("{:<10}{:>8}", "Shop", "Money");
+            for (i, money) in summary.shop_money.iter().enumerate() {
+                println!("{:<10}{:>8.2}", i + 1, money);
             }
-            GameState::Won => {
-                println!("{}", TextType::Won.stylize("===== You win! ====="));
-                println!("{}", TextType::Command.stylize("Commands: retry, quit"));
+            println!("{:<10}{:>8}", "Bought", "Runs");
+            for (card, count) in &summary.purchases {
+                println!("{:<10}{:>8}", card_text(card), count);
             }
         }
-        print!("> ");
+        Format::Csv => {
+            // one row per run
+            println!("seed,difficulty,won,floors,death,shop_money,purchases");
+            for run in &runs {
+                let death = run.death.as_ref().map(card_text).unwrap_or_default();
+                let money: Vec<String> = run.shop_money.iter().map(|money| money.to_string()).collect();
+                let purchases: Vec<String> = run.purchases.iter().map(card_text).collect();
+                println!("{},{:?},{},{},{},{},{}", run.seed, rules.difficulty, run.won, run.floors, death, money.join(" "), purchases.join(" "));
+            }
+        }
+        Format::Json => {
+            let output = serde_json::json!({ "bot": bot, "seed": seed, "rules": rules, "summary": summary, "runs": runs });
+            println!("{}", serde_json::to_string_pretty(&output).expect("ERR: Failed to serialize simulation"));
+        }
     }
+}
 
-    fn use_card(&mut self, mut room_idx: usize) {
-        if room_idx == 0 || room_idx-1 >= self.room.len() {
-            println!("{}", TextType::Bad.stylize(format!("No card in room slot {}", room_idx).as_str()));
-            return
+fn apply_action(game: &mut Game, action: Action, recording: &mut Option<(PathBuf, Replay)>) {
+    match game.apply(action) {
+        Ok(events) => {
+            if let Some((_, replay)) = recording {
+                replay.record(action);
+            }
+            for event in &events {
+                display_event(event);
+            }
         }
+        Err(e) => println!("{}", TextType::Bad.stylize(e.to_string().as_str())),
+    }
+}
 
-        match self.room[room_idx-1].card_type {
-            CardType::Joker { .. } => {
-                println!("Choose a card to destroy:");
-                print!("> ");
-                io::Write::flush(&mut io::stdout()).unwrap();
-                let mut input = String::new();
-                io::stdin().read_line(&mut input).unwrap();
-                match input.trim().parse::<usize>() {
-                    Ok(idx) => {
-                        if idx == 0 || idx-1 >= self.room.len() {
-                            println!("{}", TextType::Bad.stylize(format!("No card in room slot {}", room_idx).as_str()));
-                            return
-                        }
-                        if idx == room_idx {
-                            println!("{}", TextType::Bad.stylize("Cannot destroy itself"));
-                            return
-                        }
+#[derive(Default)]
+struct Options {
+    seed: Option<u64>,
+    resume: bool,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    solve: bool,
+    undo_depth: Option<usize>,
+    bot: Option<String>,
+    simulate: bool,
+    games: Option<usize>,
+    threads: Option<usize>,
+    format: Format,
+    no_color: bool,
+    ascii: bool,
+    accessible: bool,
+    theme: Option<String>,
+    rules: Option<String>,
+    mode: Option<String>,
+    difficulty: Option<String>,
+    #[cfg(feature = "tui")]
+    tui: bool,
+}
 
-                        let v = self.room[idx-1].get_value().div_ceil(2);
+#[derive(Default, Clone, Copy, PartialEq)]
+enum Format {
+    #[default]
+    Table,
+    Csv,
+    Json,
+}
 
-                        println!("Destroyed {}, {}", self.room[idx-1].display(), TextType::Money.stylize(format!("+${}", v).as_str()));
-                        self.money += v;
-                        self.dungeon_discard.push(self.room.remove(idx-1));
-                        if idx < room_idx {
-                            room_idx -= 1;
-                        }
-                    }
-                    Err(_) => {
-                        println!("{}", TextType::Bad.stylize("Must enter a number between 1 and 4"));
-                        return
-                    }
-                }
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "replay" => {
+                let value = args.next().ok_or("replay requires a file")?;
+                options.replay = Some(PathBuf::from(value));
             }
-            CardType::Regular { suit, rank } => match suit {
-                Suit::Clubs | Suit::Spades => {
-                    print!("Fought {} ", self.room[room_idx-1].display());
-                    if self.weapon_damage > 0 && self.weapon_durability > rank as u8 {
-                        print!("using {}, ", TextType::Diamonds.stylize(format!("{}♦", self.weapon_damage).as_str()));
-                        let d: i16 = rank as i16 - self.weapon_damage as i16;
-                        if d < 0 {
-                            self.money += d.abs() as u32;
-                            print!("{}\n", TextType::Money.stylize(format!("+${}", d.abs() as u32).as_str()));
-                        } else {
-                            self.health = cmp::max(self.health as i16 - d as i16, 0) as u8;
-                            print!("{}", TextType::Bad.stylize(format!("-{} HP\n", d as u8).as_str()));
-                        }
-                        self.weapon_durability = rank as u8;
-                    } else {
-                        print!("barehanded, ");
-                        self.health = cmp::max(self.health as i16 - rank as i16, 0) as u8;
-                        print!("{}", TextType::Bad.stylize(format!("-{} HP\n", rank as u8).as_str()));
-                    }
+            "solve" => options.solve = true,
+            "simulate" => options.simulate = true,
+            "--games" => {
+                let value = args.next().ok_or("--games requires a value")?;
+                options.games = Some(value.parse().map_err(|_| format!("Invalid game count: {}", value))?);
+            }
+            "--threads" => {
+                let value = args.next().ok_or("--threads requires a value")?;
+                options.threads = Some(value.parse().map_err(|_| format!("Invalid thread count: {}", value))?);
+            }
+            "--format" => {
+                let value = args.next().ok_or("--format requires a value")?;
+                options.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(format!("Unknown format: {} (expected table, csv or json)", value)),
+                };
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed requires a value")?;
+                options.seed = Some(value.parse().map_err(|_| format!("Invalid seed: {}", value))?);
+            }
+            "--resume" => options.resume = true,
+            "--undo-depth" => {
+                let value = args.next().ok_or("--undo-depth requires a value")?;
+                options.undo_depth = Some(value.parse().map_err(|_| format!("Invalid undo depth: {}", value))?);
+            }
+            "--hardcore" => options.undo_depth = Some(0),
+            "--no-color" => options.no_color = true,
+            "--ascii" => options.ascii = true,
+            "--accessible" => options.accessible = true,
+            "--rules" => {
+                let value = args.next().ok_or("--rules requires a preset or file")?;
+                options.rules = Some(value.clone());
+            }
+            "--mode" => {
+                let value = args.next().ok_or("--mode requires a value")?;
+                let preset = match value.as_str() {
+                    "roguelike" => "standard",
+                    "classic" => "classic",
+                    _ => return Err(format!("Unknown mode: {} (expected roguelike or classic)", value)),
+                };
+                options.mode = Some(String::from(preset));
+            }
+            "--difficulty" => {
+                let value = args.next().ok_or("--difficulty requires a value")?;
+                if !DIFFICULTIES.contains(&value.as_str()) {
+                    return Err(format!("Unknown difficulty: {} (expected one of {})", value, DIFFICULTIES.join(", ")))
                 }
-                Suit::Hearts => {
-                    if rank < Rank::Jack {
-                        self.health = cmp::min(self.health + rank as u8, cmp::max(12, self.health));
-                        println!("{}", TextType::Good.stylize(format!("+{} HP", rank as u8).as_str()));
-                    } else {
-                        let absorption = (rank as u8 - Rank::Ten as u8) * 2;
-                        self.health = 12 + absorption;
-                        println!("{}", TextType::Good.stylize(format!("Full heal + {} HP", absorption).as_str()));
-                    }
-                },
-                Suit::Diamonds => {
-                    if rank < Rank::Jack {
-                        self.weapon_damage = rank as u8;
-                        self.weapon_durability = u8::MAX;
-                        println!("Equipped {}", self.room[room_idx-1].display())
-                    } else {
-                        let repair = (rank as u8 - Rank::Ten as u8) * 2;
-                        if self.weapon_durability < u8::MAX {
-                            self.weapon_durability += repair;
-                        }
-                        println!("{}", TextType::Good.stylize(format!("Repaired {} durability", repair).as_str()));
-                    }
+                options.difficulty = Some(value.clone());
+            }
+            "--theme" => {
+                let value = args.next().ok_or("--theme requires a name or file")?;
+                options.theme = Some(value.clone());
+            }
+            #[cfg(feature = "tui")]
+            "--tui" => options.tui = true,
+            "--bot" => {
+                let value = args.next().ok_or("--bot requires a name")?;
+                if !PLAYERS.contains(&value.as_str()) {
+                    return Err(format!("Unknown bot: {} (expected one of {})", value, PLAYERS.join(", ")))
                 }
+                options.bot = Some(value.clone());
+            }
+            "--record" => {
+                let value = args.next().ok_or("--record requires a file")?;
+                options.record = Some(PathBuf::from(value));
             }
+            _ => return Err(format!("Unknown argument: {}", arg)),
         }
-
-        self.dungeon_discard.push(self.room.remove(room_idx-1));
-        self.fled = false;
     }
+    #[cfg(feature = "tui")]
+    if options.tui && options.bot.is_some() {
+        return Err(String::from("--tui cannot be combined with --bot"))
+    }
+    let presets = [&options.rules, &options.mode, &options.difficulty].iter().filter(|preset| preset.is_some()).count();
+    if presets > 1 {
+        return Err(String::from("Only one of --rules, --mode and --difficulty can be given"))
+    }
+    if options.resume && presets > 0 {
+        return Err(String::from("--rules, --mode and --difficulty cannot be combined with --resume"))
+    }
+    if options.resume && options.record.is_some() {
+        return Err(String::from("--record cannot be combined with --resume"))
+    }
+    Ok(options)
+}
 
-    fn flee(&mut self) {
-        if self.room.len() < 4 {
-            println!("{}", TextType::Bad.stylize("Can only flee from a full room"));
-            return
-        }
-        if self.fled {
-            println!("{}", TextType::Bad.stylize("Cannot flee twice in a row"));
-            return
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", TextType::Bad.stylize(e.as_str()));
+            eprintln!("Usage: dungeoncards [--seed N] [--resume] [--record FILE] [--undo-depth N | --hardcore] [--bot NAME] [--tui] [--no-color] [--ascii] [--accessible] [--theme NAME|FILE] [--rules PRESET|FILE] [--mode roguelike|classic] [--difficulty easy|normal|hard|nightmare]");
+            eprintln!("       dungeoncards replay FILE");
+            eprintln!("       dungeoncards solve [--seed N]");
+            eprintln!("       dungeoncards simulate [--games N] [--bot NAME] [--threads T] [--seed N] [--format table|csv|json] [--difficulty NAME]");
+            std::process::exit(2);
         }
+    };
 
-        for _i in 0..4 {
-            self.dungeon.push(self.room.pop().expect("ERR: Too few cards in room"));
+    let palette = if options.no_color { Palette::Plain } else { Palette::detect() };
+    if palette == Palette::Plain {
+        colored::control::set_override(false);
+    }
+    let theme = match load_theme(options.theme.as_deref()) {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
         }
-        self.fled = true;
+    };
+    let _ = OUTPUT.set(Output { palette, ascii: options.ascii, accessible: options.accessible, theme });
 
-        println!("{}", TextType::Bad.stylize("Fled from room!"));
+    if let Some(path) = &options.replay {
+        if let Err(e) = run_replay(path) {
+            eprintln!("{}", TextType::Bad.stylize(e.as_str()));
+            std::process::exit(1);
+        }
+        return
     }
 
-    fn buy_card(&mut self, shop_idx: usize) {
-        if shop_idx == 0 || shop_idx-1 >= self.shop_stock.len() {
-            println!("{}", TextType::Bad.stylize(format!("No card in shop slot {}", shop_idx).as_str()));
-            return
+    let rules = match load_rules(options.rules.as_deref().or(options.mode.as_deref()).or(options.difficulty.as_deref())) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}", TextType::Bad.stylize(e.as_str()));
+            std::process::exit(2);
         }
+    };
 
-        let card = &self.shop_stock[shop_idx-1];
-        if self.money >= card.get_value() {
-            println!("{}, {} added to dungeon", TextType::Bad.stylize(format!("-${}", card.get_value()).as_str()), card.display());
-            self.money -= card.get_value();
-            self.dungeon.push(self.shop_stock.remove(shop_idx-1));
-        } else {
-            println!("{}", TextType::Bad.stylize("Can't afford card"));
-        }
+    if options.simulate {
+        run_simulate(&options, rules);
+        return
     }
 
-    // debug
-    fn steal_card(&mut self, shop_idx: usize) {
-        if shop_idx == 0 || shop_idx-1 >= self.shop_stock.len() {
-            println!("{}", TextType::Bad.stylize(format!("No card in shop slot {}", shop_idx).as_str()));
-            return
+    let mut game = match options.seed {
+        Some(seed) => Game::with_rules(seed, rules),
+        None => Game::with_rules(rand::random(), rules),
+    };
+    if options.solve {
+        run_solve(&game);
+        return
+    }
+    if options.resume {
+        match load_game(DEFAULT_SAVE) {
+            Ok(saved) => game = saved,
+            Err(e) => {
+                eprintln!("{}", TextType::Bad.stylize(e.as_str()));
+                std::process::exit(1);
+            }
         }
+    }
+    if let Some(depth) = options.undo_depth {
+        game.undo_depth = depth;
+    }
+    let mut recording = options.record.map(|path| (path, Replay::new(&game)));
 
-        self.dungeon.push(self.shop_stock.remove(shop_idx-1));
+    #[cfg(feature = "tui")]
+    if options.tui {
+        if let Err(e) = tui::run(&mut game, recording.as_mut().map(|(_, replay)| replay)) {
+            eprintln!("{}", TextType::Bad.stylize(e.to_string().as_str()));
+            std::process::exit(1);
+        }
+        if let Some((path, mut replay)) = recording {
+            write_replay(&mut replay, &game, &path);
+        }
+        return
     }
-}
 
-fn main() {
-    let mut game = Game::new();
-    game.start_floor();
-    game.refresh_room(true);
+    let mut tracking = false;
+    let mut bot = options.bot.and_then(|name| player::by_name(&name, game.seed));
 
     loop {
-        game.display();
-        io::Write::flush(&mut io::stdout()).unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let parts: Vec<&str> = input.trim().split_whitespace().collect();
-
-        match game.state {
-            GameState::Floor => {
-                match parts.as_slice() {
-                    ["use", card] => {
-                        match card.parse::<usize>() {
-                            Ok(idx) => game.use_card(idx),
-                            Err(_) => println!("{}", TextType::Bad.stylize("Must enter a number between 1 and 4")),
-                        }
-                    }
-                    ["flee"] => game.flee(),
-                    ["quit"] => break,
-                    ["win"] => { // debug
-                        println!("{}", TextType::Good.stylize("Floor complete!"));
-
-                        if game.bosses.is_empty() {
-                            game.state = GameState::Won;
-                        } else {
-                            game.state = GameState::Shop;
-                            for _i in 0..cmp::max(game.shop.len(), 4) {
-                                game.shop_stock.push(game.shop.remove(0));
-                            }
-                        }
-                    }
-                    _ => println!("{}", TextType::Bad.stylize("Invalid command")),
-                }
-
-                game.refresh_room(false);
+        display(&game, tracking);
+        if let Some(bot) = &mut bot {
+            // bots play a single run to the end
+            if matches!(game.state, GameState::Lost | GameState::Won) {
+                println!();
+                break
             }
-            GameState::Lost | GameState::Won => {
-                match parts.as_slice() {
-                    ["retry"] => {
-                        game = Game::new();
-                        game.start_floor();
-                        game.refresh_room(true);
-                    }
-                    ["quit"] => break,
-                    _ => println!("{}", TextType::Bad.stylize("Invalid command")),
+            let action = bot.choose(&game.view(true));
+            println!("{}", command_text(&action));
+            apply_action(&mut game, action, &mut recording);
+            continue
+        }
+
+        let Some(input) = read_input() else {
+            println!();
+            break
+        };
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        match parts.as_slice() {
+            ["quit"] => break,
+            ["save"] | ["save", _] => {
+                let name = parts.get(1).unwrap_or(&DEFAULT_SAVE);
+                match save_game(&game, name) {
+                    Ok(()) => println!("{}", TextType::Notification.stylize(format!("Saved to {}", name).as_str())),
+                    Err(e) => println!("{}", TextType::Bad.stylize(e.as_str())),
                 }
+                continue
             }
-            GameState::Shop => {
-                match parts.as_slice() {
-                    ["buy", card] => {
-                        match card.parse::<usize>() {
-                            Ok(idx) => game.buy_card(idx),
-                            Err(_) => println!("{}", TextType::Bad.stylize("Must enter a number between 1 and 4")),
-                        }
-                    }
-                    ["steal", card] => { // debug
-                        match card.parse::<usize>() {
-                            Ok(idx) => game.steal_card(idx),
-                            Err(_) => println!("{}", TextType::Bad.stylize("Must enter a number between 1 and 4")),
+            ["track"] => {
+                tracking = !tracking;
+                let text = if tracking { "Tracker on" } else { "Tracker off" };
+                println!("{}", TextType::Notification.stylize(text));
+                continue
+            }
+            ["hint"] if matches!(game.state, GameState::Floor | GameState::Choosing { .. }) => {
+                show_hint(&game);
+                continue
+            }
+            ["load"] | ["load", _] => {
+                let name = parts.get(1).unwrap_or(&DEFAULT_SAVE);
+                match load_game(name) {
+                    Ok(saved) => {
+                        // a loaded game can't be reproduced from the recorded seed
+                        if let Some((path, mut replay)) = recording.take() {
+                            write_replay(&mut replay, &game, &path);
+                            println!("{}", TextType::Notification.stylize("Recording stopped"));
                         }
+                        game = saved;
+                        println!("{}", TextType::Notification.stylize(format!("Loaded {}", name).as_str()));
                     }
-                    ["continue"] => {
-                        game.shop_discard.append(&mut game.shop_stock);
-                        if game.shop.is_empty() {
-                            println!("{}", TextType::Notification.stylize("Shop restocked"));
-                            game.shop.append(&mut game.shop_discard);
-                            game.shop.shuffle(&mut rand::rng());
-                        }
-                        println!("{} & {} added to dungeon", game.bosses[0].display(), game.bosses[1].display());
-                        game.dungeon.push(game.bosses.remove(0));
-                        game.dungeon.push(game.bosses.remove(0));
-
-                        game.state = GameState::Floor;
-                        game.start_floor();
-                        game.refresh_room(true);
-                    },
-                    ["quit"] => break,
-                    _ => println!("{}", TextType::Bad.stylize("Invalid command")),
+                    Err(e) => println!("{}", TextType::Bad.stylize(e.as_str())),
                 }
+                continue
             }
+            _ => {}
         }
+
+        let Some(action) = parse_action(&game, &parts) else {
+            continue
+        };
+        apply_action(&mut game, action, &mut recording);
+    }
+
+    if let Some((path, mut replay)) = recording {
+        write_replay(&mut replay, &game, &path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dungeoncards::GameBuilder;
+
+    #[test]
+    fn ansi256_grayscale_ramp() {
+        assert_eq!(ansi256(0, 0, 0), 16);
+        assert_eq!(ansi256(7, 7, 7), 16);
+        assert_eq!(ansi256(8, 8, 8), 232);
+        assert_eq!(ansi256(128, 128, 128), 244);
+        assert_eq!(ansi256(247, 247, 247), 255);
+        assert_eq!(ansi256(248, 248, 248), 231);
+        assert_eq!(ansi256(255, 255, 255), 231);
+    }
+
+    #[test]
+    fn ansi256_cube_edges() {
+        assert_eq!(ansi256(255, 0, 0), 196);
+        assert_eq!(ansi256(0, 255, 0), 46);
+        assert_eq!(ansi256(0, 0, 255), 21);
+        // 48 and 115 start the second and third levels
+        assert_eq!(ansi256(47, 48, 114), 16 + 6 + 1);
+        assert_eq!(ansi256(115, 0, 0), 16 + 36 * 2);
+        assert_eq!(ansi256(255, 255, 0), 226);
+    }
+
+    #[test]
+    fn detects_palette() {
+        assert_eq!(Palette::from_env(true, Some("truecolor"), Some("xterm-256color")), Palette::Plain);
+        assert_eq!(Palette::from_env(false, Some("truecolor"), Some("xterm")), Palette::TrueColor);
+        assert_eq!(Palette::from_env(false, Some("24bit"), None), Palette::TrueColor);
+        assert_eq!(Palette::from_env(false, Some("yes"), Some("xterm-256color")), Palette::Ansi256);
+        assert_eq!(Palette::from_env(false, None, Some("xterm")), Palette::Ansi16);
+        assert_eq!(Palette::from_env(false, None, None), Palette::Ansi16);
+    }
+
+    #[test]
+    fn joker_arguments_need_a_joker() {
+        let game = GameBuilder::new()
+            .room(vec![Card::joker(JokerColor::Red), Card::new(Suit::Clubs, Rank::Nine)])
+            .build()
+            .unwrap();
+        assert_eq!(parse_action(&game, &["use", "2"]), Some(Action::Use(2)));
+        assert_eq!(parse_action(&game, &["use", "2", "1"]), None);
+        assert_eq!(parse_action(&game, &["use", "1", "2"]), Some(Action::UseJoker { slot: 1, target: 2, effect: JokerEffect::Heal }));
     }
 }
@@ -0,0 +1,269 @@
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::action::{Action, JokerEffect};
+use crate::boss::Ability;
+use crate::card::{Card, CardType, Rank, Suit};
+use crate::game::GameState;
+use crate::hint::hint;
+use crate::rules::Mode;
+use crate::view::PlayerView;
+
+pub trait Player {
+    fn name(&self) -> &str;
+    fn choose(&mut self, view: &PlayerView) -> Action;
+}
+
+pub const PLAYERS: [&str; 4] = ["random", "greedy", "cautious", "search"];
+
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Player>> {
+    match name {
+        "random" => Some(Box::new(RandomPlayer::new(seed))),
+        "greedy" => Some(Box::new(GreedyPlayer)),
+        "cautious" => Some(Box::new(CautiousPlayer)),
+        "search" => Some(Box::new(SearchPlayer::new(seed))),
+        _ => None,
+    }
+}
+
+// damage the monster would deal if fought right now
+fn damage(view: &PlayerView, card: &Card) -> Option<u8> {
+    match card.card_type {
+        CardType::Regular { suit: Suit::Clubs | Suit::Spades, rank } => {
+            let value = view.rules.rank_value(rank);
+            let ability = Ability::of(card, &view.rules);
+            if view.weapon_damage > 0 && view.weapon_durability > value {
+                let weapon = if ability == Some(Ability::Armored) { view.weapon_damage / 2 } else { view.weapon_damage };
+                Some(value.saturating_sub(weapon))
+            } else if ability == Some(Ability::Venom) {
+                Some(value + 2)
+            } else {
+                Some(value)
+            }
+        }
+        _ => None,
+    }
+}
+
+// weapons and heals first, then the monster that hurts least
+fn priority(view: &PlayerView, card: &Card) -> i32 {
+    if let Some(damage) = damage(view, card) {
+        return if damage >= view.health { -100 } else { 50 - damage as i32 }
+    }
+    match card.card_type {
+        CardType::Regular { suit: Suit::Diamonds, rank } if rank < Rank::Jack => {
+            if rank as u8 > view.weapon_damage || view.weapon_durability < rank as u8 { 100 + rank as i32 } else { 10 }
+        }
+        CardType::Regular { suit: Suit::Diamonds, .. } => {
+            if view.weapon_durability < u8::MAX { 90 } else { 5 }
+        }
+        // a second heart in a classic room heals nothing
+        CardType::Regular { suit: Suit::Hearts, .. } if view.rules.mode == Mode::Classic && view.healed => 1,
+        CardType::Regular { suit: Suit::Hearts, rank } if rank < Rank::Jack => {
+            if view.health < view.rules.max_health { 80 + rank as i32 } else { 5 }
+        }
+        CardType::Regular { suit: Suit::Hearts, .. } => {
+            if view.health < view.rules.max_health { 95 } else { 5 }
+        }
+        _ => 0,
+    }
+}
+
+// the worst monster goes first, destroyed rather than buried if the weapon gains from it
+fn joker_priority(view: &PlayerView, target: usize, effect: JokerEffect) -> i32 {
+    let card = &view.room[target - 1];
+    let Some(damage) = damage(view, card) else {
+        return 0
+    };
+    let bonus = match effect {
+        JokerEffect::Heal => 1,
+        JokerEffect::Forge if view.weapon_damage < 10 => 1,
+        JokerEffect::Forge | JokerEffect::Bury => 0,
+    };
+    60 + damage as i32 * 2 + bonus
+}
+
+// buys the most expensive card it can afford
+fn shop_action(view: &PlayerView) -> Action {
+    view.legal_actions.iter()
+        .filter_map(|action| match action {
+            Action::Buy(slot) => Some((view.shop_stock[slot - 1].1, *action)),
+            _ => None,
+        })
+        .max_by_key(|(price, _)| *price)
+        .map_or(Action::Continue, |(_, action)| action)
+}
+
+fn greedy_action(view: &PlayerView) -> Action {
+    if view.state == GameState::Shop {
+        return shop_action(view)
+    }
+
+    let mut best: Option<(i32, Action)> = None;
+    for &action in &view.legal_actions {
+        let priority = match action {
+            Action::Use(slot) => priority(view, &view.room[slot - 1]),
+            Action::UseJoker { target, effect, .. } | Action::Target { target, effect } => joker_priority(view, target, effect),
+            Action::Retry => 0,
+            _ => continue,
+        };
+        if best.is_none_or(|(best, _)| priority > best) {
+            best = Some((priority, action));
+        }
+    }
+    best.map_or(Action::Flee, |(_, action)| action)
+}
+
+pub struct RandomPlayer {
+    rng: ChaCha8Rng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        RandomPlayer { rng: ChaCha8Rng::seed_from_u64(seed) }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn choose(&mut self, view: &PlayerView) -> Action {
+        *view.legal_actions.choose(&mut self.rng).expect("ERR: No legal actions")
+    }
+}
+
+pub struct GreedyPlayer;
+
+impl Player for GreedyPlayer {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn choose(&mut self, view: &PlayerView) -> Action {
+        greedy_action(view)
+    }
+}
+
+pub struct CautiousPlayer;
+
+impl Player for CautiousPlayer {
+    fn name(&self) -> &str {
+        "cautious"
+    }
+
+    // flees whenever the monsters in the room could kill
+    fn choose(&mut self, view: &PlayerView) -> Action {
+        if view.legal_actions.contains(&Action::Flee) {
+            let room_damage: u32 = view.room.iter().filter_map(|card| damage(view, card)).map(u32::from).sum();
+            if room_damage >= view.health as u32 {
+                return Action::Flee
+            }
+        }
+        greedy_action(view)
+    }
+}
+
+pub struct SearchPlayer {
+    rng: ChaCha8Rng,
+    samples: usize,
+}
+
+impl SearchPlayer {
+    pub fn new(seed: u64) -> Self {
+        SearchPlayer { rng: ChaCha8Rng::seed_from_u64(seed), samples: 50 }
+    }
+}
+
+impl Player for SearchPlayer {
+    fn name(&self) -> &str {
+        "search"
+    }
+
+    fn choose(&mut self, view: &PlayerView) -> Action {
+        // plays as if the remaining cards could come in any order
+        match view.floor_game().and_then(|game| hint(&game, self.samples, self.rng.random())) {
+            Some(hint) => hint.best.action,
+            None => greedy_action(view),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::GameBuilder;
+    use crate::game::Game;
+    use crate::rules::Ruleset;
+
+    fn play(player: &mut dyn Player, seed: u64) -> Game {
+        let mut game = Game::with_seed(seed);
+        game.undo_depth = 0;
+        for _i in 0..1000 {
+            if matches!(game.state, GameState::Lost | GameState::Won) {
+                break
+            }
+            let action = player.choose(&game.view(true));
+            game.apply(action).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn every_player_finishes_a_run() {
+        for name in PLAYERS {
+            let mut player = by_name(name, 1).unwrap();
+            assert_eq!(player.name(), name);
+            let game = play(player.as_mut(), 2);
+            assert!(matches!(game.state, GameState::Lost | GameState::Won), "{} got stuck", name);
+        }
+    }
+
+    #[test]
+    fn greedy_skips_wasted_heart() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Hearts, Rank::Five), Card::new(Suit::Clubs, Rank::Four)])
+            .rules(Ruleset::preset("classic").unwrap())
+            .health(10)
+            .healed(true)
+            .build()
+            .unwrap();
+        assert_eq!(GreedyPlayer.choose(&game.view(true)), Action::Use(2));
+    }
+
+    #[test]
+    fn greedy_equips_and_heals_first() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Clubs, Rank::Four), Card::new(Suit::Hearts, Rank::Five), Card::new(Suit::Diamonds, Rank::Seven)])
+            .health(10)
+            .build()
+            .unwrap();
+        assert_eq!(GreedyPlayer.choose(&game.view(true)), Action::Use(3));
+
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Clubs, Rank::Four), Card::new(Suit::Hearts, Rank::Five)])
+            .health(10)
+            .build()
+            .unwrap();
+        assert_eq!(GreedyPlayer.choose(&game.view(true)), Action::Use(2));
+    }
+
+    #[test]
+    fn cautious_flees_deadly_room() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Clubs, Rank::Nine), Card::new(Suit::Spades, Rank::Eight), Card::new(Suit::Hearts, Rank::Five), Card::new(Suit::Diamonds, Rank::Four)])
+            .dungeon(vec![Card::new(Suit::Clubs, Rank::Four); 4])
+            .allow_duplicates(true)
+            .build()
+            .unwrap();
+        assert_eq!(CautiousPlayer.choose(&game.view(true)), Action::Flee);
+        assert_ne!(GreedyPlayer.choose(&game.view(true)), Action::Flee);
+    }
+
+    #[test]
+    fn unknown_player() {
+        assert!(by_name("psychic", 0).is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::action::{Action, ActionError};
+use crate::game::Game;
+use crate::rules::Ruleset;
+use crate::save;
+
+pub const REPLAY_VERSION: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub undo_depth: usize,
+    pub rules: Ruleset,
+    pub actions: Vec<Action>,
+    pub checksum: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Corrupt(serde_json::Error),
+    Incompatible { version: u32 },
+    Rejected { step: usize, action: Action, error: ActionError },
+    Mismatch { expected: u64, found: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access replay: {}", e),
+            Self::Corrupt(e) => write!(f, "Replay is corrupt: {}", e),
+            Self::Incompatible { version } => write!(f, "Replay version {} is not supported (expected {})", version, REPLAY_VERSION),
+            Self::Rejected { step, action, error } => write!(f, "Step {} ({:?}) was rejected: {}", step, action, error),
+            Self::Mismatch { expected, found } => write!(f, "Final state {:016x} does not match recorded {:016x}", found, expected),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// FNV-1a over the saved game, stable across platforms and compiler versions
+pub fn checksum(game: &Game) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in save::to_string(game).bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+impl Replay {
+    pub fn new(game: &Game) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            seed: game.seed,
+            undo_depth: game.undo_depth,
+            rules: game.rules,
+            actions: vec![],
+            checksum: None,
+        }
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.actions.push(action);
+        self.checksum = None;
+    }
+
+    pub fn finish(&mut self, game: &Game) {
+        self.checksum = Some(checksum(game));
+    }
+
+    pub fn start(&self) -> Game {
+        let mut game = Game::with_rules(self.seed, self.rules);
+        game.undo_depth = self.undo_depth;
+        game
+    }
+
+    // plays every recorded action and checks the result against the checksum
+    pub fn run(&self) -> Result<Game, ReplayError> {
+        let mut game = self.start();
+        for (step, action) in self.actions.iter().enumerate() {
+            game.apply(*action).map_err(|error| ReplayError::Rejected { step: step + 1, action: *action, error })?;
+        }
+        self.verify(&game)?;
+        Ok(game)
+    }
+
+    pub fn verify(&self, game: &Game) -> Result<(), ReplayError> {
+        match self.checksum {
+            Some(expected) if expected != checksum(game) => {
+                Err(ReplayError::Mismatch { expected, found: checksum(game) })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("ERR: Replay is always serializable")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ReplayError> {
+        let header: ReplayHeader = serde_json::from_str(text).map_err(ReplayError::Corrupt)?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::Incompatible { version: header.version })
+        }
+        let replay: Replay = serde_json::from_str(text).map_err(ReplayError::Corrupt)?;
+        replay.rules.validate().map_err(|e| ReplayError::Corrupt(serde::de::Error::custom(e)))?;
+        Ok(replay)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_reproduces_game() {
+        let mut game = Game::with_seed(11);
+        let mut replay = Replay::new(&game);
+        for action in [Action::Flee, Action::Use(2), Action::Undo, Action::Use(1)] {
+            if game.apply(action).is_ok() {
+                replay.record(action);
+            }
+        }
+        replay.finish(&game);
+
+        let replayed = Replay::from_json(&replay.to_json()).unwrap().run().unwrap();
+        assert_eq!(checksum(&replayed), checksum(&game));
+    }
+
+    #[test]
+    fn replay_detects_mismatch() {
+        let game = Game::with_seed(11);
+        let mut replay = Replay::new(&game);
+        replay.finish(&game);
+        replay.seed = 12;
+        assert!(matches!(replay.run(), Err(ReplayError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let mut replay = Replay::new(&Game::with_seed(11));
+        replay.rules.room_size = 2;
+        replay.rules.restock_threshold = 3;
+        assert!(matches!(Replay::from_json(&replay.to_json()), Err(ReplayError::Corrupt(_))));
+    }
+}
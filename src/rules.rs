@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::card::{Card, CardType, JokerColor, Rank};
+
+pub const PRESETS: [&str; 4] = ["standard", "quick", "marathon", "classic"];
+pub const DIFFICULTIES: [&str; 4] = ["easy", "normal", "hard", "nightmare"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Roguelike,
+    // the original solitaire: one floor of a 44 card deck, aces high and one heal per room
+    Classic,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ruleset {
+    pub mode: Mode,
+    // only a label, the other fields decide how hard the game is
+    pub difficulty: Difficulty,
+    pub max_health: u8,
+    pub starting_money: u32,
+    pub room_size: usize,
+    // the room is restocked once it holds this many cards or fewer
+    pub restock_threshold: usize,
+    pub min_dungeon_rank: u8,
+    pub max_dungeon_rank: u8,
+    // red cards from this rank up are sold in the shop, along with the jokers
+    pub min_shop_rank: u8,
+    // black cards from this rank up are held back as bosses
+    pub min_boss_rank: u8,
+    // the black joker costs more for its choice of effects
+    pub red_joker_value: u32,
+    pub black_joker_value: u32,
+    pub shop_size: usize,
+    pub bosses_per_floor: usize,
+    pub boss_abilities: bool,
+    // bosses arrive in a random order instead of weakest first
+    pub shuffle_bosses: bool,
+    // no limit beyond never fleeing twice in a row when unset
+    pub flees_per_floor: Option<u8>,
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Corrupt(toml::de::Error),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access ruleset: {}", e),
+            Self::Corrupt(e) => write!(f, "Ruleset is corrupt: {}", e),
+            Self::Invalid(reason) => write!(f, "Ruleset is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl From<io::Error> for RulesError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            mode: Mode::Roguelike,
+            difficulty: Difficulty::Normal,
+            max_health: 12,
+            starting_money: 5,
+            room_size: 4,
+            restock_threshold: 1,
+            min_dungeon_rank: 4,
+            max_dungeon_rank: 9,
+            min_shop_rank: 10,
+            min_boss_rank: 10,
+            red_joker_value: 14,
+            black_joker_value: 16,
+            shop_size: 4,
+            bosses_per_floor: 2,
+            boss_abilities: true,
+            shuffle_bosses: false,
+            flees_per_floor: None,
+        }
+    }
+}
+
+impl Ruleset {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Ruleset::default()),
+            // all eight bosses arrive after the first floor
+            "quick" => Some(Ruleset { bosses_per_floor: 8, ..Ruleset::default() }),
+            // the low cards join the dungeon and the bosses come one at a time
+            "marathon" => Some(Ruleset { min_dungeon_rank: 2, bosses_per_floor: 1, ..Ruleset::default() }),
+            "classic" => Some(Ruleset { mode: Mode::Classic, max_health: 20, starting_money: 0, ..Ruleset::default() }),
+            "easy" => Some(Ruleset {
+                difficulty: Difficulty::Easy,
+                max_health: 15,
+                starting_money: 10,
+                ..Ruleset::default()
+            }),
+            "normal" => Some(Ruleset::default()),
+            // the twos and threes fill out the dungeon
+            "hard" => Some(Ruleset {
+                difficulty: Difficulty::Hard,
+                max_health: 10,
+                starting_money: 3,
+                min_dungeon_rank: 2,
+                shop_size: 3,
+                shuffle_bosses: true,
+                flees_per_floor: Some(2),
+                ..Ruleset::default()
+            }),
+            "nightmare" => Some(Ruleset {
+                difficulty: Difficulty::Nightmare,
+                max_health: 8,
+                starting_money: 0,
+                min_dungeon_rank: 2,
+                shop_size: 2,
+                shuffle_bosses: true,
+                flees_per_floor: Some(1),
+                ..Ruleset::default()
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn rank_value(&self, rank: Rank) -> u8 {
+        match (self.mode, rank) {
+            (Mode::Classic, Rank::Ace) => 14,
+            _ => rank as u8,
+        }
+    }
+
+    // what a card costs in the shop and is worth to a joker
+    pub fn value(&self, card: &Card) -> u32 {
+        match card.card_type {
+            CardType::Joker { color: JokerColor::Red } => self.red_joker_value,
+            CardType::Joker { color: JokerColor::Black } => self.black_joker_value,
+            CardType::Regular { rank, .. } => self.rank_value(rank) as u32,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), RulesError> {
+        // leaves room for overheal within a u8
+        if self.max_health == 0 || self.max_health > 99 {
+            return Err(RulesError::Invalid("max_health must be between 1 and 99"))
+        }
+        // jokers heal and forge half their value within a u8
+        if self.red_joker_value > 99 || self.black_joker_value > 99 {
+            return Err(RulesError::Invalid("joker values must be at most 99"))
+        }
+        if self.bosses_per_floor == 0 {
+            return Err(RulesError::Invalid("bosses_per_floor must be at least 1"))
+        }
+        if self.room_size == 0 || self.shop_size == 0 {
+            return Err(RulesError::Invalid("room_size and shop_size must be at least 1"))
+        }
+        if self.restock_threshold >= self.room_size {
+            return Err(RulesError::Invalid("restock_threshold must be below room_size"))
+        }
+        if self.min_dungeon_rank == 0 || self.min_dungeon_rank > self.max_dungeon_rank || self.max_dungeon_rank > 13 {
+            return Err(RulesError::Invalid("dungeon ranks must be an ascending range within 1 to 13"))
+        }
+        if self.min_shop_rank <= self.max_dungeon_rank || self.min_boss_rank <= self.max_dungeon_rank {
+            return Err(RulesError::Invalid("shop and boss ranks must be above the dungeon ranks"))
+        }
+        Ok(())
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, RulesError> {
+        let rules: Ruleset = toml::from_str(text).map_err(RulesError::Corrupt)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, RulesError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        for name in PRESETS.iter().chain(&DIFFICULTIES) {
+            Ruleset::preset(name).unwrap().validate().unwrap();
+        }
+        assert_eq!(Ruleset::preset("standard"), Some(Ruleset::default()));
+        assert_eq!(Ruleset::preset("nightmare").unwrap().difficulty, Difficulty::Nightmare);
+    }
+
+    #[test]
+    fn partial_toml_keeps_defaults() {
+        let rules = Ruleset::from_toml("max_health = 20\nbosses_per_floor = 3\n").unwrap();
+        assert_eq!(rules, Ruleset { max_health: 20, bosses_per_floor: 3, ..Ruleset::default() });
+    }
+
+    #[test]
+    fn invalid_toml() {
+        assert!(matches!(Ruleset::from_toml("room_size = 1\n"), Err(RulesError::Invalid(_))));
+        assert!(matches!(Ruleset::from_toml("max_dungeon_rank = 11\n"), Err(RulesError::Invalid(_))));
+        assert!(matches!(Ruleset::from_toml("lives = 3\n"), Err(RulesError::Corrupt(_))));
+        assert!(matches!(Ruleset::from_toml("bosses_per_floor = 0\n"), Err(RulesError::Invalid(_))));
+        assert!(matches!(Ruleset::from_toml("max_health = 250\n"), Err(RulesError::Invalid(_))));
+        assert!(matches!(Ruleset::from_toml("black_joker_value = 500\n"), Err(RulesError::Invalid(_))));
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::Game;
+
+// bump whenever the serialized Game layout changes
+pub const SAVE_VERSION: u32 = 7;
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    game: Game,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Corrupt(serde_json::Error),
+    Incompatible { version: u32 },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access save: {}", e),
+            Self::Corrupt(e) => write!(f, "Save is corrupt: {}", e),
+            Self::Incompatible { version } => write!(f, "Save version {} is not supported (expected {})", version, SAVE_VERSION),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub fn to_string(game: &Game) -> String {
+    serde_json::to_string(&SaveFileRef { version: SAVE_VERSION, game }).expect("ERR: Game is always serializable")
+}
+
+pub fn from_str(text: &str) -> Result<Game, SaveError> {
+    let header: SaveHeader = serde_json::from_str(text).map_err(SaveError::Corrupt)?;
+    if header.version != SAVE_VERSION {
+        return Err(SaveError::Incompatible { version: header.version })
+    }
+
+    let file: SaveFile = serde_json::from_str(text).map_err(SaveError::Corrupt)?;
+    file.game.rules.validate().map_err(|e| SaveError::Corrupt(serde::de::Error::custom(e)))?;
+    Ok(file.game)
+}
+
+pub fn write(game: &Game, path: &Path) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, to_string(game))?;
+    Ok(())
+}
+
+pub fn read(path: &Path) -> Result<Game, SaveError> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+
+    #[test]
+    fn round_trip_keeps_rng() {
+        let mut game = Game::with_seed(7);
+        game.apply(Action::Use(1)).unwrap();
+        let mut loaded = from_str(&to_string(&game)).unwrap();
+        assert_eq!(to_string(&loaded), to_string(&game));
+
+        game.apply(Action::Use(1)).unwrap();
+        loaded.apply(Action::Use(1)).unwrap();
+        assert_eq!(to_string(&loaded), to_string(&game));
+    }
+
+    #[test]
+    fn rejects_bad_saves() {
+        assert!(matches!(from_str("not json"), Err(SaveError::Corrupt(_))));
+        assert!(matches!(from_str(r#"{"version": 99, "game": {}}"#), Err(SaveError::Incompatible { version: 99 })));
+        assert!(matches!(from_str(&format!(r#"{{"version": {}, "game": {{}}}}"#, SAVE_VERSION)), Err(SaveError::Corrupt(_))));
+
+        let mut game = Game::with_seed(7);
+        game.rules.room_size = 2;
+        game.rules.restock_threshold = 3;
+        assert!(matches!(from_str(&to_string(&game)), Err(SaveError::Corrupt(_))));
+    }
+}
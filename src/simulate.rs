@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::thread;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+use crate::card::Card;
+use crate::event::GameEvent;
+use crate::game::{Game, GameState};
+use crate::player::by_name;
+use crate::rules::Ruleset;
+
+// guards against bots that never finish a run
+const MAX_STEPS: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunStats {
+    pub seed: u64,
+    pub won: bool,
+    pub floors: usize,
+    // card that dealt the killing blow
+    pub death: Option<Card>,
+    // money on entering each shop
+    pub shop_money: Vec<u32>,
+    pub purchases: Vec<Card>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub games: usize,
+    pub wins: usize,
+    // number of runs by floors cleared
+    pub floors: Vec<usize>,
+    pub deaths: Vec<(Card, usize)>,
+    // average money on entering each shop, over the runs that got there
+    pub shop_money: Vec<f64>,
+    pub purchases: Vec<(Card, usize)>,
+}
+
+impl Summary {
+    pub fn new(runs: &[RunStats]) -> Self {
+        let mut floors = Vec::new();
+        let mut deaths = HashMap::new();
+        let mut shop_money: Vec<(u64, usize)> = Vec::new();
+        let mut purchases = HashMap::new();
+        for run in runs {
+            if floors.len() <= run.floors {
+                floors.resize(run.floors + 1, 0);
+            }
+            floors[run.floors] += 1;
+            if let Some(card) = &run.death {
+                *deaths.entry(card.clone()).or_insert(0) += 1;
+            }
+            for (i, money) in run.shop_money.iter().enumerate() {
+                if shop_money.len() <= i {
+                    shop_money.push((0, 0));
+                }
+                shop_money[i].0 += *money as u64;
+                shop_money[i].1 += 1;
+            }
+            for card in &run.purchases {
+                *purchases.entry(card.clone()).or_insert(0) += 1;
+            }
+        }
+
+        Summary {
+            games: runs.len(),
+            wins: runs.iter().filter(|run| run.won).count(),
+            floors,
+            deaths: sorted(deaths),
+            shop_money: shop_money.into_iter().map(|(total, runs)| total as f64 / runs as f64).collect(),
+            purchases: sorted(purchases),
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0
+        }
+        self.wins as f64 / self.games as f64
+    }
+
+    pub fn average_floors(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0
+        }
+        let total: usize = self.floors.iter().enumerate().map(|(floors, runs)| floors * runs).sum();
+        total as f64 / self.games as f64
+    }
+}
+
+// most frequent first
+fn sorted(counts: HashMap<Card, usize>) -> Vec<(Card, usize)> {
+    let mut counts: Vec<(Card, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+    counts
+}
+
+pub fn play(bot: &str, rules: Ruleset, seed: u64) -> Option<RunStats> {
+    let mut player = by_name(bot, seed)?;
+    let mut game = Game::with_rules(seed, rules);
+    game.undo_depth = 0;
+
+    let mut stats = RunStats { seed, won: false, floors: 0, death: None, shop_money: Vec::new(), purchases: Vec::new() };
+    let mut last_fought = None;
+    for _i in 0..MAX_STEPS {
+        if matches!(game.state, GameState::Lost | GameState::Won) {
+            break
+        }
+        let action = player.choose(&game.view(true));
+        let Ok(events) = game.apply(action) else {
+            break
+        };
+        for event in events {
+            match event {
+                GameEvent::Fought { card, .. } => last_fought = Some(card),
+                GameEvent::FloorComplete => stats.floors += 1,
+                GameEvent::Purchased { card, .. } => stats.purchases.push(card),
+                GameEvent::Lost => stats.death = last_fought.take(),
+                GameEvent::Won => stats.won = true,
+                _ => {}
+            }
+        }
+        if game.state == GameState::Shop && stats.shop_money.len() < stats.floors {
+            stats.shop_money.push(game.money);
+        }
+    }
+    Some(stats)
+}
+
+// plays `games` runs spread over `threads` threads, in a deterministic order
+pub fn simulate(bot: &str, rules: Ruleset, games: usize, threads: usize, seed: u64) -> Option<Vec<RunStats>> {
+    by_name(bot, seed)?;
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let seeds: Vec<u64> = (0..games).map(|_i| rng.random()).collect();
+    let threads = threads.clamp(1, games.max(1));
+    let chunk = games.div_ceil(threads).max(1);
+
+    let runs = thread::scope(|scope| {
+        let handles: Vec<_> = seeds.chunks(chunk)
+            .map(|seeds| scope.spawn(move || seeds.iter().filter_map(|seed| play(bot, rules, *seed)).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("ERR: Simulation thread panicked")).collect()
+    });
+    Some(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_is_deterministic() {
+        let single = simulate("greedy", Ruleset::default(), 6, 1, 7).unwrap();
+        let parallel = simulate("greedy", Ruleset::default(), 6, 3, 7).unwrap();
+        assert_eq!(single.len(), 6);
+        let seeds: Vec<u64> = single.iter().map(|run| run.seed).collect();
+        assert_eq!(seeds, parallel.iter().map(|run| run.seed).collect::<Vec<u64>>());
+        assert_eq!(single.iter().map(|run| run.floors).collect::<Vec<_>>(), parallel.iter().map(|run| run.floors).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn summary_counts_runs() {
+        let runs = simulate("cautious", Ruleset::default(), 8, 2, 3).unwrap();
+        let summary = Summary::new(&runs);
+        assert_eq!(summary.games, 8);
+        assert_eq!(summary.floors.iter().sum::<usize>(), 8);
+        let deaths: usize = summary.deaths.iter().map(|(_, count)| count).sum();
+        assert_eq!(deaths + summary.wins, 8);
+        assert!(summary.shop_money.len() <= summary.floors.len());
+    }
+
+    #[test]
+    fn unknown_bot() {
+        assert!(simulate("psychic", Ruleset::default(), 1, 1, 0).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use crate::action::Action;
+use crate::boss::Ability;
+use crate::card::{Card, CardType, Suit};
+use crate::game::{Game, GameState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub clearable: bool,
+    pub max_health: u8,
+    pub max_money: u32,
+    // clears the floor with the most money, then the most health
+    pub actions: Vec<Action>,
+}
+
+// money, the discard pile and the weapon stack only matter while a thief or a summoning boss
+// is left to meet, so they stay out of the key otherwise
+#[derive(Hash, PartialEq, Eq)]
+struct Key {
+    room: Vec<u8>,
+    dungeon: Vec<u8>,
+    // monsters a summoning boss could bring back
+    discard: Vec<u8>,
+    // joins the discard pile when the weapon is replaced or shattered
+    weapon_stack: Vec<u8>,
+    // what a thief could steal
+    money: Option<u32>,
+    health: u8,
+    weapon_damage: u8,
+    weapon_durability: u8,
+    fled: bool,
+    flees: u8,
+    healed: bool,
+    state: GameState,
+}
+
+fn code(card: &Card) -> u8 {
+    match card.card_type {
+        CardType::Regular { suit, rank } => (suit as u8) << 4 | rank as u8,
+        CardType::Joker { color } => 0x40 | color as u8,
+    }
+}
+
+// whether a boss with the ability is still in the room or the dungeon
+fn ahead(game: &Game, ability: Ability) -> bool {
+    game.room.iter().chain(&game.dungeon).any(|card| game.ability(card) == Some(ability))
+}
+
+impl Key {
+    fn new(game: &Game) -> Self {
+        let summon = ahead(game, Ability::Summon);
+        let discard = match summon {
+            true => game.dungeon_discard.iter()
+                .filter(|card| matches!(card.card_type, CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. }))
+                .map(code)
+                .collect(),
+            false => vec![],
+        };
+        let weapon_stack = match summon {
+            true => game.weapon_stack.iter().map(code).collect(),
+            false => vec![],
+        };
+        Key {
+            room: game.room.iter().map(code).collect(),
+            dungeon: game.dungeon.iter().map(code).collect(),
+            discard,
+            weapon_stack,
+            money: ahead(game, Ability::Thief).then_some(game.money),
+            health: game.health,
+            weapon_damage: game.weapon_damage,
+            weapon_durability: game.weapon_durability,
+            fled: game.fled,
+            flees: game.flees,
+            healed: game.healed,
+            state: game.state.clone(),
+        }
+    }
+}
+
+// best outcome over every line that clears the floor from a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Value {
+    // a thief can leave this negative
+    money_gained: i64,
+    health: u8,
+    max_health: u8,
+}
+
+impl Value {
+    fn line(&self) -> (i64, u8) {
+        (self.money_gained, self.health)
+    }
+}
+
+#[derive(Default)]
+pub struct Solver {
+    memo: HashMap<Key, Option<Value>>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // number of distinct positions evaluated so far
+    pub fn positions(&self) -> usize {
+        self.memo.len()
+    }
+
+    pub fn solve(&mut self, game: &Game) -> Solution {
+        // piles that can't affect the rest of the floor only slow the search down
+        let mut game = game.snapshot();
+        game.undo_depth = 0;
+        if !ahead(&game, Ability::Summon) {
+            game.dungeon_discard.clear();
+            game.weapon_stack.clear();
+        }
+        game.shop.clear();
+        game.shop_stock.clear();
+        game.shop_discard.clear();
+
+        let Some(value) = self.search(&game) else {
+            return Solution { clearable: false, max_health: 0, max_money: game.money, actions: vec![] }
+        };
+
+        let max_money = (game.money as i64 + value.money_gained) as u32;
+        let mut actions = vec![];
+        let mut target = value.line();
+        while matches!(game.state, GameState::Floor | GameState::Choosing { .. }) {
+            for (action, child, gained) in Self::children(&game) {
+                let Some(child_value) = self.search(&child) else {
+                    continue
+                };
+                if (child_value.money_gained + gained, child_value.health) == target {
+                    actions.push(action);
+                    target = child_value.line();
+                    game = child;
+                    break
+                }
+            }
+        }
+
+        Solution { clearable: true, max_health: value.max_health, max_money, actions }
+    }
+
+    fn children(game: &Game) -> Vec<(Action, Game, i64)> {
+        let mut children = vec![];
+        for action in game.legal_actions() {
+            let mut child = game.clone();
+            if child.apply(action).is_ok() {
+                let gained = child.money as i64 - game.money as i64;
+                children.push((action, child, gained));
+            }
+        }
+        children
+    }
+
+    fn search(&mut self, game: &Game) -> Option<Value> {
+        match game.state {
+            GameState::Shop | GameState::Won => {
+                return Some(Value { money_gained: 0, health: game.health, max_health: game.health })
+            }
+            GameState::Lost => return None,
+            GameState::Floor | GameState::Choosing { .. } => {}
+        }
+
+        // remembering every other position keeps memory down without revisiting much
+        let key = game.room.len().is_multiple_of(2).then(|| Key::new(game));
+        if let Some(value) = key.as_ref().and_then(|key| self.memo.get(key)) {
+            return *value
+        }
+
+        let mut best: Option<Value> = None;
+        for (_, child, gained) in Self::children(game) {
+            let Some(value) = self.search(&child) else {
+                continue
+            };
+            let value = Value { money_gained: value.money_gained + gained, ..value };
+            best = Some(match best {
+                None => value,
+                Some(best) => Value {
+                    max_health: best.max_health.max(value.max_health),
+                    ..if value.line() > best.line() { value } else { best }
+                },
+            });
+        }
+
+        if let Some(key) = key {
+            self.memo.insert(key, best);
+        }
+        best
+    }
+}
+
+pub fn solve(game: &Game) -> Solution {
+    Solver::new().solve(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::JokerEffect;
+    use crate::builder::GameBuilder;
+    use crate::card::{JokerColor, Rank};
+
+    fn play(game: &Game, actions: &[Action]) -> Game {
+        let mut game = game.clone();
+        for action in actions {
+            game.apply(*action).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn finds_best_line() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Clubs, Rank::Nine), Card::new(Suit::Diamonds, Rank::Eight), Card::new(Suit::Spades, Rank::Four), Card::new(Suit::Hearts, Rank::Five)])
+            .dungeon(vec![Card::new(Suit::Clubs, Rank::Seven), Card::new(Suit::Hearts, Rank::Six)])
+            .bosses(vec![Card::new(Suit::Clubs, Rank::Ten)])
+            .build()
+            .unwrap();
+
+        let solution = solve(&game);
+        assert!(solution.clearable);
+
+        let end = play(&game, &solution.actions);
+        assert_eq!(end.state, GameState::Shop);
+        assert_eq!(end.money, solution.max_money);
+        // equip first, then fight the 9, 7 and 4 in descending order for 0 + 1 + 4 money
+        assert_eq!(solution.max_money, 5 + 5);
+        assert_eq!(solution.max_health, 12);
+    }
+
+    #[test]
+    fn joker_can_remove_a_deadly_monster() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::joker(JokerColor::Black), Card::new(Suit::Clubs, Rank::Six)])
+            .health(7)
+            .build()
+            .unwrap();
+
+        let solution = solve(&game);
+        assert!(solution.clearable);
+        // forging the 9 into a weapon beats fighting the 6 barehanded
+        assert_eq!(solution.actions[0], Action::UseJoker { slot: 2, target: 1, effect: JokerEffect::Forge });
+        assert_eq!(play(&game, &solution.actions).health, 6);
+    }
+
+    #[test]
+    fn reports_unclearable_floor() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::new(Suit::Clubs, Rank::Eight)])
+            .health(12)
+            .build()
+            .unwrap();
+
+        let solution = solve(&game);
+        assert!(!solution.clearable);
+        assert!(solution.actions.is_empty());
+    }
+
+    #[test]
+    fn thief_lowers_money() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Clubs, Rank::King), Card::new(Suit::Diamonds, Rank::Nine)])
+            .money(10)
+            .build()
+            .unwrap();
+
+        let solution = solve(&game);
+        assert!(solution.clearable);
+        assert_eq!(solution.max_money, 5);
+        assert_eq!(play(&game, &solution.actions).health, 8);
+    }
+
+    #[test]
+    fn jack_summons_from_weapon_stack() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Diamonds, Rank::Four), Card::new(Suit::Hearts, Rank::Two)])
+            .dungeon(vec![Card::new(Suit::Spades, Rank::Jack), Card::new(Suit::Hearts, Rank::Three), Card::new(Suit::Hearts, Rank::Four), Card::new(Suit::Hearts, Rank::Five)])
+            .weapon(9, 8)
+            .weapon_stack(vec![Card::new(Suit::Clubs, Rank::Eight)])
+            .build()
+            .unwrap();
+
+        // equipping the 4 discards the 8 for the jack to bring back
+        let solution = solve(&game);
+        assert!(solution.clearable);
+        assert!(matches!(play(&game, &solution.actions).state, GameState::Shop | GameState::Won));
+    }
+
+    #[test]
+    fn flees_when_needed() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Spades, Rank::Nine), Card::new(Suit::Clubs, Rank::Nine), Card::new(Suit::Spades, Rank::Eight), Card::new(Suit::Clubs, Rank::Eight)])
+            .dungeon(vec![Card::new(Suit::Diamonds, Rank::Nine), Card::new(Suit::Hearts, Rank::Four), Card::new(Suit::Hearts, Rank::Five), Card::new(Suit::Hearts, Rank::Six)])
+            .build()
+            .unwrap();
+
+        let solution = solve(&game);
+        assert!(solution.clearable);
+        assert_eq!(solution.actions[0], Action::Flee);
+        assert_eq!(play(&game, &solution.actions).state, GameState::Won);
+    }
+}
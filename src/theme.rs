@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use colored::{Color, ColoredString, Colorize};
+use serde::Deserialize;
+
+use crate::{rgb, TextType};
+
+pub const THEMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+fn builtin_source(name: &str) -> Option<&'static str> {
+    match name {
+        "dark" => Some(include_str!("themes/dark.toml")),
+        "light" => Some(include_str!("themes/light.toml")),
+        "high-contrast" => Some(include_str!("themes/high-contrast.toml")),
+        _ => None,
+    }
+}
+
+// a color name understood by colored, or #rrggbb
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ThemeColor(Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if let Some(hex) = value.strip_prefix('#') {
+            let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(ThemeColor(Color::TrueColor { r, g, b })),
+                _ => Err(format!("Invalid color: {}", value)),
+            }
+        }
+        Color::from_str(&value).map(ThemeColor).map_err(|_| format!("Invalid color: {}", value))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    pub color: Option<ThemeColor>,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl Style {
+    pub fn apply(self, text: &str) -> ColoredString {
+        let mut text = text.normal();
+        if self.bold {
+            text = text.bold();
+        }
+        if self.italic {
+            text = text.italic();
+        }
+        match self.color {
+            Some(ThemeColor(Color::TrueColor { r, g, b })) => rgb(text, r, g, b),
+            Some(ThemeColor(color)) => text.color(color),
+            None => text,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    styles: HashMap<TextType, Style>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin("dark").expect("ERR: Missing dark theme")
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Self> {
+        Some(Theme::parse(builtin_source(name)?).expect("ERR: Invalid built-in theme"))
+    }
+
+    // text types the theme leaves out keep their dark theme style
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let parse_styles = |source: &str| toml::from_str::<HashMap<TextType, Style>>(source).map_err(|e| e.to_string());
+        let mut styles = parse_styles(builtin_source("dark").expect("ERR: Missing dark theme"))?;
+        styles.extend(parse_styles(source)?);
+        Ok(Theme { styles })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Theme::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // a built-in name or the path of a theme file
+    pub fn load(name: &str) -> Result<Self, String> {
+        match Theme::builtin(name) {
+            Some(theme) => Ok(theme),
+            None if Path::new(name).exists() => Theme::read(Path::new(name)),
+            None => Err(format!("Unknown theme: {} (expected {} or a theme file)", name, THEMES.join(", "))),
+        }
+    }
+
+    pub fn style(&self, text_type: TextType) -> Style {
+        self.styles.get(&text_type).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_style_every_text_type() {
+        for name in THEMES {
+            let theme = Theme::builtin(name).unwrap();
+            assert_eq!(theme.styles.len(), 16, "{} is incomplete", name);
+        }
+    }
+
+    #[test]
+    fn partial_theme_falls_back_to_dark() {
+        let theme = Theme::parse("[spades]\ncolor = \"#ffffff\"\nbold = true\n").unwrap();
+        assert_eq!(theme.style(TextType::Spades), Style { color: Some(ThemeColor(Color::TrueColor { r: 255, g: 255, b: 255 })), bold: true, italic: false });
+        assert_eq!(theme.style(TextType::Clubs), Theme::default().style(TextType::Clubs));
+    }
+
+    #[test]
+    fn invalid_theme() {
+        assert!(Theme::parse("[spades]\ncolor = \"#12\"\n").is_err());
+        assert!(Theme::parse("[spades]\ncolor = \"plaid\"\n").is_err());
+        assert!(Theme::parse("[sparkles]\nbold = true\n").is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use std::io;
+
+use dungeoncards::{Ability, Action, Card, CardType, Game, GameEvent, GameState, JokerColor, Mode, Replay, Suit};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::rank_symbol;
+
+const LOG_SIZE: usize = 200;
+
+struct App<'a> {
+    game: &'a mut Game,
+    replay: Option<&'a mut Replay>,
+    selected: usize,
+    // index into the effects of the joker being played
+    effect: usize,
+    log: Vec<Line<'static>>,
+}
+
+pub fn run(game: &mut Game, replay: Option<&mut Replay>) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut app = App { game, replay, selected: 0, effect: 0, log: vec![] };
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+fn card_color(card: &Card) -> Color {
+    match card.card_type {
+        CardType::Regular { suit: Suit::Hearts, .. } => Color::Red,
+        CardType::Regular { suit: Suit::Diamonds, .. } => Color::LightRed,
+        CardType::Regular { suit: Suit::Clubs | Suit::Spades, .. } => Color::Gray,
+        CardType::Joker { color: JokerColor::Red } => Color::Magenta,
+        CardType::Joker { color: JokerColor::Black } => Color::Blue,
+    }
+}
+
+fn card_label(card: &Card) -> (String, String) {
+    match card.card_type {
+        CardType::Regular { suit, rank } => {
+            let suit_symbol = match suit {
+                Suit::Hearts => "♥",
+                Suit::Diamonds => "♦",
+                Suit::Clubs => "♣",
+                Suit::Spades => "♠",
+            };
+            (rank_symbol(rank), String::from(suit_symbol))
+        }
+        CardType::Joker { color: JokerColor::Red } => (String::from("Jo"), String::from("☼")),
+        CardType::Joker { color: JokerColor::Black } => (String::from("Jo"), String::from("☾")),
+    }
+}
+
+fn card_name(card: &Card) -> String {
+    let (rank, suit) = card_label(card);
+    format!("{}{}", rank, suit)
+}
+
+// rank in the corners and the suit in the middle, with a boss ability on top
+fn card_art(card: &Card, ability: Option<Ability>, footer: Option<String>, selected: bool) -> Paragraph<'static> {
+    let (rank, suit) = card_label(card);
+    let style = Style::default().fg(card_color(card));
+    let lines = vec![
+        Line::from(Span::styled(format!("{:<7}", rank), style)),
+        Line::from(""),
+        Line::from(Span::styled(format!("   {}   ", suit), style.add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled(format!("{:>7}", rank), style)),
+    ];
+    let border = if selected { Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD) } else { Style::default() };
+    let mut block = Block::default().borders(Borders::ALL).border_style(border);
+    if let Some(ability) = ability {
+        block = block.title(Span::styled(ability.to_string(), Style::default().fg(Color::Red)));
+    }
+    if let Some(footer) = footer {
+        block = block.title_bottom(footer);
+    }
+    Paragraph::new(lines).block(block)
+}
+
+fn event_line(event: &GameEvent) -> Line<'static> {
+    let (text, color) = match event {
+        GameEvent::Fought { card, weapon, damage, money } => {
+            let using = match weapon {
+                Some(weapon) => format!("using {}♦", weapon),
+                None => String::from("barehanded"),
+            };
+            if *money > 0 {
+                (format!("Fought {} {}, +${}", card_name(card), using, money), Color::Yellow)
+            } else {
+                (format!("Fought {} {}, -{} HP", card_name(card), using, damage), Color::Red)
+            }
+        }
+        GameEvent::Healed { amount, full: false, .. } => (format!("+{} HP", amount), Color::Green),
+        GameEvent::Healed { amount, full: true, .. } => (format!("Full heal + {} HP", amount), Color::Green),
+        GameEvent::Wasted { card } => (format!("Discarded {}, already healed in this room", card_name(card)), Color::Red),
+        GameEvent::Cursed { boss, card } => (format!("Discarded {}, {} cursed it", card_name(card), card_name(boss)), Color::Red),
+        GameEvent::Equipped { card } => (format!("Equipped {}", card_name(card)), Color::White),
+        GameEvent::Repaired { amount, .. } => (format!("Repaired {} durability", amount), Color::Green),
+        GameEvent::Destroyed { card } => (format!("Destroyed {}", card_name(card)), Color::White),
+        GameEvent::Forged { card, damage } => (format!("Forged {} into a {}♦ weapon", card_name(card), damage), Color::LightRed),
+        GameEvent::Buried { card } => (format!("Buried {} at the bottom of the dungeon", card_name(card)), Color::White),
+        GameEvent::Fled => (String::from("Fled from room!"), Color::Red),
+        GameEvent::RoomRestocked => (String::from("Restocked room"), Color::Cyan),
+        GameEvent::FloorComplete => (String::from("Floor complete!"), Color::Green),
+        GameEvent::Lost => (String::from("You lost"), Color::Red),
+        GameEvent::Won => (String::from("You win!"), Color::Green),
+        GameEvent::Purchased { card, price } => (format!("-${}, {} added to dungeon", price, card_name(card)), Color::Yellow),
+        GameEvent::ShopRestocked => (String::from("Shop restocked"), Color::Cyan),
+        GameEvent::BossesAdded(bosses) => {
+            let names: Vec<String> = bosses.iter().map(card_name).collect();
+            (format!("{} added to dungeon", names.join(" & ")), Color::White)
+        }
+        GameEvent::Summoned { boss, card } => (format!("{} summoned {} back into the dungeon", card_name(boss), card_name(card)), Color::Red),
+        GameEvent::Robbed { boss, money } => (format!("{} stole ${}", card_name(boss), money), Color::Red),
+        GameEvent::Shattered { boss } => (format!("{} shattered your weapon", card_name(boss)), Color::Red),
+        GameEvent::Undone => (String::from("Undid last action"), Color::Cyan),
+    };
+    Line::from(Span::styled(text, Style::default().fg(color)))
+}
+
+impl App<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue
+            };
+            if key.kind != KeyEventKind::Press {
+                continue
+            }
+            if key.code == KeyCode::Char('q') {
+                return Ok(())
+            }
+            if let Some(action) = self.action_for(key.code) {
+                self.apply(action);
+            }
+        }
+    }
+
+    // how many cards the rules allow on screen at once
+    fn capacity(&self) -> usize {
+        match self.game.state {
+            GameState::Shop => self.game.rules.shop_size,
+            _ => self.game.rules.room_size,
+        }
+    }
+
+    // cards the number keys and arrows select from
+    fn slots(&self) -> usize {
+        match self.game.state {
+            GameState::Shop => self.game.shop_stock.len(),
+            _ => self.game.room.len(),
+        }
+    }
+
+    fn action_for(&mut self, key: KeyCode) -> Option<Action> {
+        let slot = match key {
+            KeyCode::Left => {
+                self.selected = self.selected.saturating_sub(1);
+                return None
+            }
+            KeyCode::Right => {
+                self.selected = (self.selected + 1).min(self.slots().saturating_sub(1));
+                return None
+            }
+            KeyCode::Enter => self.selected + 1,
+            KeyCode::Char(c @ '1'..='9') if c as usize - ('0' as usize) <= self.capacity() => c as usize - '0' as usize,
+            KeyCode::Char('u') => return Some(Action::Undo),
+            KeyCode::Char('f') if self.game.state == GameState::Floor => return Some(Action::Flee),
+            KeyCode::Char('c') if self.game.state == GameState::Shop => return Some(Action::Continue),
+            KeyCode::Esc if matches!(self.game.state, GameState::Choosing { .. }) => return Some(Action::Cancel),
+            KeyCode::Char('e') => {
+                if let GameState::Choosing { joker } = self.game.state {
+                    self.effect = (self.effect + 1) % self.game.joker_effects(joker).len().max(1);
+                }
+                return None
+            }
+            KeyCode::Char('r') if matches!(self.game.state, GameState::Lost | GameState::Won) => return Some(Action::Retry),
+            _ => return None,
+        };
+        match self.game.state {
+            GameState::Floor => Some(Action::Use(slot)),
+            GameState::Choosing { joker } => {
+                let effect = *self.game.joker_effects(joker).get(self.effect)?;
+                Some(Action::Target { target: slot, effect })
+            }
+            GameState::Shop => Some(Action::Buy(slot)),
+            GameState::Lost | GameState::Won => None,
+        }
+    }
+
+    fn apply(&mut self, action: Action) {
+        match self.game.apply(action) {
+            Ok(events) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.record(action);
+                }
+                self.log.extend(events.iter().map(event_line));
+            }
+            Err(e) => self.log.push(Line::from(Span::styled(e.to_string(), Style::default().fg(Color::Red)))),
+        }
+        if self.log.len() > LOG_SIZE {
+            self.log.drain(..self.log.len() - LOG_SIZE);
+        }
+        self.selected = self.selected.min(self.slots().saturating_sub(1));
+        if !matches!(self.game.state, GameState::Choosing { .. }) {
+            self.effect = 0;
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, cards, stats, log, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(7),
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        frame.render_widget(Paragraph::new(self.header()), header);
+        self.draw_cards(frame, cards);
+        self.draw_stats(frame, stats);
+
+        // newest events at the bottom
+        let height = log.height.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self.log.iter().skip(self.log.len().saturating_sub(height)).cloned().map(ListItem::new).collect();
+        frame.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title("Log")), log);
+
+        frame.render_widget(Paragraph::new(self.commands()).style(Style::default().fg(Color::DarkGray)), footer);
+    }
+
+    fn header(&self) -> Line<'static> {
+        let title = match self.game.state {
+            GameState::Floor => format!("Dungeon - {} card(s) left", self.game.dungeon.len()),
+            GameState::Choosing { joker } => match self.game.joker_effects(joker).get(self.effect) {
+                Some(effect) => format!("Joker: {} - {}", effect.name(), effect.description()),
+                None => String::from("Choose a card for the joker"),
+            },
+            GameState::Shop => String::from("Shop"),
+            GameState::Lost => String::from("Game over"),
+            GameState::Won => String::from("You win!"),
+        };
+        let mut text = format!("{}  |  Seed: {}  |  {:?}", title, self.game.seed, self.game.rules.difficulty);
+        if let Some(flees) = self.game.flees_left() {
+            text.push_str(&format!("  |  Flees left: {}", flees));
+        }
+        let selected = self.game.room.get(self.selected).filter(|_| self.game.state == GameState::Floor);
+        if let Some(ability) = selected.and_then(|card| self.game.ability(card)) {
+            text.push_str(&format!("  |  {}: {}", ability, ability.description()));
+        }
+        if self.game.rules.mode == Mode::Classic {
+            if let Some(score) = self.game.score() {
+                text.push_str(&format!("  |  Score: {}", score));
+            }
+        }
+        if self.game.undo_used {
+            text.push_str(" (undo used)");
+        }
+        Line::from(Span::styled(text, Style::default().add_modifier(Modifier::BOLD)))
+    }
+
+    fn commands(&self) -> String {
+        let keys = format!("1-{}/←→/enter", self.capacity().min(9));
+        match self.game.state {
+            GameState::Floor => format!("{}: use card   f: flee   u: undo   q: quit", keys),
+            GameState::Choosing { .. } => format!("{}: choose card   e: next effect   esc: cancel   u: undo   q: quit", keys),
+            GameState::Shop => format!("{}: buy card   c: continue   u: undo   q: quit", keys),
+            GameState::Lost | GameState::Won => String::from("r: retry   u: undo   q: quit"),
+        }
+    }
+
+    fn draw_cards(&self, frame: &mut Frame, area: Rect) {
+        let slots: Vec<(Card, Option<String>)> = match self.game.state {
+            GameState::Shop => self.game.shop_stock.iter().map(|card| (card.clone(), Some(format!("${}", self.game.rules.value(card))))).collect(),
+            GameState::Lost | GameState::Won => vec![],
+            _ => self.game.room.iter().map(|card| (card.clone(), None)).collect(),
+        };
+        let areas = Layout::horizontal(vec![Constraint::Length(9); self.capacity()]).split(area);
+        for (i, (card, footer)) in slots.iter().enumerate().take(areas.len()) {
+            let joker = matches!(self.game.state, GameState::Choosing { joker } if joker == i + 1);
+            let footer = footer.clone().or_else(|| Some(format!("{}", i + 1)));
+            frame.render_widget(card_art(card, self.game.ability(card), footer, i == self.selected || joker), areas[i]);
+        }
+    }
+
+    fn draw_stats(&self, frame: &mut Frame, area: Rect) {
+        let [health, money, weapon] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(area);
+
+        let max_health = self.game.rules.max_health;
+        let third = self.game.health as u32 * 3;
+        let color = if third <= max_health as u32 {
+            Color::Red
+        } else if third <= max_health as u32 * 2 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let health_gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Health"))
+            .gauge_style(Style::default().fg(color))
+            .ratio((self.game.health as f64 / max_health as f64).min(1.0))
+            .label(format!("{}/{} HP", self.game.health, max_health));
+        frame.render_widget(health_gauge, health);
+
+        // full once every card in the shop is affordable
+        let money_gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Money"))
+            .gauge_style(Style::default().fg(Color::Yellow))
+            .ratio((self.game.money as f64 / self.game.rules.black_joker_value.max(self.game.rules.red_joker_value).max(13) as f64).min(1.0))
+            .label(format!("${}", self.game.money));
+        frame.render_widget(money_gauge, money);
+
+        let text = match (self.game.weapon_damage, self.game.weapon_durability) {
+            (0, _) => String::from("None"),
+            (damage, u8::MAX) => format!("{}♦ (fresh)", damage),
+            (damage, durability) => format!("{}♦ ({} durability)", damage, durability),
+        };
+        let text = match self.game.weapon_stack.last() {
+            Some(card) => format!("{}, last slain {}", text, card_name(card)),
+            None => text,
+        };
+        let weapon_panel = Paragraph::new(Span::styled(text, Style::default().fg(Color::LightRed)))
+            .block(Block::default().borders(Borders::ALL).title("Weapon"));
+        frame.render_widget(weapon_panel, weapon);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::action::Action;
+use crate::builder::GameBuilder;
+use crate::card::{Card, Rank, Suit};
+use crate::game::{Game, GameState};
+use crate::rules::Ruleset;
+
+// what a player at the table can see, without the dungeon order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub state: GameState,
+    pub room: Vec<Card>,
+    pub dungeon_count: usize,
+    // most recent last, which a summoning boss brings back from
+    pub discard: Vec<Card>,
+    pub health: u8,
+    pub money: u32,
+    pub weapon_damage: u8,
+    pub weapon_durability: u8,
+    // monsters slain with the current weapon
+    pub weapon_stack: Vec<Card>,
+    pub fled: bool,
+    pub flees: u8,
+    // whether a heart already healed in this room
+    pub healed: bool,
+    // cards for sale with their prices
+    pub shop_stock: Vec<(Card, u32)>,
+    pub bosses: usize,
+    pub legal_actions: Vec<Action>,
+    pub rules: Ruleset,
+    // sorted dungeon contents, which a careful player can deduce from the discard
+    pub remaining: Option<Vec<Card>>,
+}
+
+impl PlayerView {
+    pub fn new(game: &Game, counting: bool) -> Self {
+        let remaining = counting.then(|| {
+            let mut cards = game.dungeon.clone();
+            cards.sort();
+            cards
+        });
+        PlayerView {
+            state: game.state.clone(),
+            room: game.room.clone(),
+            dungeon_count: game.dungeon.len(),
+            discard: game.dungeon_discard.clone(),
+            health: game.health,
+            money: game.money,
+            weapon_damage: game.weapon_damage,
+            weapon_durability: game.weapon_durability,
+            weapon_stack: game.weapon_stack.clone(),
+            fled: game.fled,
+            flees: game.flees,
+            healed: game.healed,
+            shop_stock: game.shop_stock.iter().map(|card| (card.clone(), game.rules.value(card))).collect(),
+            bosses: game.bosses.len(),
+            legal_actions: game.legal_actions(),
+            rules: game.rules,
+            remaining,
+        }
+    }
+
+    // a game consistent with the view for the rest of the floor, with the remaining cards in sorted order
+    pub fn floor_game(&self) -> Option<Game> {
+        // only how many bosses are left matters before the shop
+        let bosses = vec![Card::new(Suit::Spades, Rank::King); self.bosses];
+        let mut game = GameBuilder::new()
+            .dungeon(self.remaining.clone()?)
+            .dungeon_discard(self.discard.clone())
+            .room(self.room.clone())
+            .bosses(bosses)
+            .health(self.health)
+            .money(self.money)
+            .weapon(self.weapon_damage, self.weapon_durability)
+            .weapon_stack(self.weapon_stack.clone())
+            .fled(self.fled)
+            .flees(self.flees)
+            .healed(self.healed)
+            .state(self.state.clone())
+            .rules(self.rules)
+            .allow_duplicates(true)
+            .build()
+            .ok()?;
+        game.undo_depth = 0;
+        Some(game)
+    }
+}
+
+impl Game {
+    pub fn view(&self, counting: bool) -> PlayerView {
+        PlayerView::new(self, counting)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::GameEvent;
+
+    #[test]
+    fn view_hides_dungeon_order() {
+        let mut game = Game::with_seed(5);
+        let view = game.view(true);
+        game.dungeon.reverse();
+        assert_eq!(game.view(true), view);
+        assert_eq!(game.view(false).remaining, None);
+        assert_eq!(view.dungeon_count, game.dungeon.len());
+        assert_eq!(view.remaining.unwrap().len(), game.dungeon.len());
+    }
+
+    #[test]
+    fn shop_prices() {
+        let game = GameBuilder::new()
+            .shop_stock(vec![Card::new(Suit::Hearts, Rank::Queen)])
+            .state(GameState::Shop)
+            .build()
+            .unwrap();
+        let view = game.view(false);
+        assert_eq!(view.shop_stock, vec![(Card::new(Suit::Hearts, Rank::Queen), 12)]);
+        assert_eq!(view.legal_actions, vec![Action::Continue]);
+    }
+
+    #[test]
+    fn floor_game_matches_view() {
+        let game = Game::with_seed(9);
+        let view = game.view(true);
+        let sample = view.floor_game().unwrap().view(true);
+        assert_eq!(sample.room, view.room);
+        assert_eq!(sample.remaining, view.remaining);
+        assert_eq!(sample.legal_actions, view.legal_actions);
+        assert!(game.view(false).floor_game().is_none());
+    }
+
+    #[test]
+    fn floor_game_keeps_classic_state() {
+        let mut game = Game::with_rules(4, Ruleset::preset("classic").unwrap());
+        game.healed = true;
+        game.weapon_stack = vec![Card::new(Suit::Clubs, Rank::Ace)];
+        let sample = game.view(true).floor_game().unwrap();
+        assert!(sample.healed);
+        assert_eq!(sample.weapon_stack, game.weapon_stack);
+    }
+
+    #[test]
+    fn floor_game_keeps_discard_and_bosses() {
+        let game = GameBuilder::new()
+            .room(vec![Card::new(Suit::Hearts, Rank::Two), Card::new(Suit::Hearts, Rank::Three)])
+            .dungeon(vec![Card::new(Suit::Spades, Rank::Jack)])
+            .dungeon_discard(vec![Card::new(Suit::Clubs, Rank::Six)])
+            .bosses(vec![Card::new(Suit::Clubs, Rank::Ten)])
+            .build()
+            .unwrap();
+        let mut sample = game.view(true).floor_game().unwrap();
+        assert_eq!(sample.dungeon_discard, game.dungeon_discard);
+        assert_eq!(sample.bosses.len(), 1);
+
+        // the jack dealt into the room brings the 6 back
+        let events = sample.apply(Action::Use(1)).unwrap();
+        assert!(events.contains(&GameEvent::Summoned { boss: Card::new(Suit::Spades, Rank::Jack), card: Card::new(Suit::Clubs, Rank::Six) }));
+        assert_eq!(sample.room[1], Card::new(Suit::Spades, Rank::Jack));
+    }
+}